@@ -3,8 +3,32 @@ use std::path::{Path, PathBuf};
 use tauri::State;
 
 use crate::models::note::FileTreeNode;
+use crate::services::parser;
 use crate::AppState;
 
+/// Default theme used when the caller doesn't pick one (or picks an unknown
+/// name); a dark theme bundled with every `syntect` default theme set.
+const DEFAULT_THEME: &str = "base16-ocean.dark";
+
+/// Render a note's markdown to HTML, with fenced code blocks highlighted
+/// using the named `syntect` theme (falls back to `DEFAULT_THEME`).
+#[tauri::command]
+pub fn render_note_html(
+    content: String,
+    theme: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let theme_name = theme.as_deref().unwrap_or(DEFAULT_THEME);
+    let theme = state
+        .theme_set
+        .themes
+        .get(theme_name)
+        .or_else(|| state.theme_set.themes.get(DEFAULT_THEME))
+        .ok_or("No syntax highlighting themes available")?;
+
+    Ok(parser::render_to_html(&content, &state.syntax_set, theme))
+}
+
 /// Read the contents of a file
 #[tauri::command]
 pub fn read_file(path: String, state: State<'_, AppState>) -> Result<String, String> {
@@ -1,97 +1,258 @@
-use tauri::State;
+use tauri::{AppHandle, Emitter, State};
 
+use crate::models::git_error::GitError;
 use crate::models::git_status::*;
 use crate::services::git_service;
 use crate::AppState;
 
+/// Key for a cached read-only git result: unique per vault/operation/args.
+fn cache_key(vault_path: &str, op: &str, args: &str) -> String {
+    format!("{vault_path}:{op}:{args}")
+}
+
+/// Run blocking git2 work off the async runtime's worker thread, so polling
+/// commands like `git_status` never stall the UI.
+async fn run_blocking<T, F>(f: F) -> Result<T, GitError>
+where
+    F: FnOnce() -> Result<T, GitError> + Send + 'static,
+    T: Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(f)
+        .await
+        .unwrap_or_else(|e| {
+            Err(GitError {
+                class: crate::models::git_error::ErrorClass::Io,
+                message: format!("Background git task failed: {e}"),
+                code: None,
+            })
+        })
+}
+
+/// A cached read was a JSON string from a previous call; deserializing it
+/// should never fail since we only ever write what we just serialized.
+fn from_cache<T: serde::de::DeserializeOwned>(cached: &str) -> Result<T, GitError> {
+    serde_json::from_str(cached).map_err(|e| GitError {
+        class: crate::models::git_error::ErrorClass::Io,
+        message: format!("Failed to read cached git result: {e}"),
+        code: None,
+    })
+}
+
 #[tauri::command]
-pub fn git_status(state: State<'_, AppState>) -> Result<Vec<FileStatus>, String> {
-    let vault = state.vault_path.lock().unwrap();
-    let vault_path = vault.as_ref().ok_or("No vault open")?;
+pub async fn git_status(state: State<'_, AppState>) -> Result<Vec<FileStatus>, GitError> {
+    let vault_path = state
+        .vault_path
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or_else(GitError::no_vault)?;
+    let key = cache_key(&vault_path, "status", "");
+
+    if let Some(cached) = state.git_cache.get(&key) {
+        return from_cache(&cached);
+    }
+
+    let result = run_blocking(move || {
+        let repo = git_service::open_repo(&vault_path)?;
+        git_service::get_status(&repo)
+    })
+    .await?;
+
+    if let Ok(json) = serde_json::to_string(&result) {
+        state.git_cache.insert(key, json);
+    }
+    Ok(result)
+}
 
-    let repo = git_service::open_repo(vault_path).map_err(|e| e.to_string())?;
-    git_service::get_status(&repo).map_err(|e| e.to_string())
+/// One-call status badge: branch, upstream ahead/behind, and file/stash
+/// counts. See [`git_service::repo_summary`] for how each field is derived.
+#[tauri::command]
+pub async fn git_repo_summary(state: State<'_, AppState>) -> Result<RepoSummary, GitError> {
+    let vault_path = state
+        .vault_path
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or_else(GitError::no_vault)?;
+    let key = cache_key(&vault_path, "repo_summary", "");
+
+    if let Some(cached) = state.git_cache.get(&key) {
+        return from_cache(&cached);
+    }
+
+    let result = run_blocking(move || {
+        let mut repo = git_service::open_repo(&vault_path)?;
+        git_service::repo_summary(&mut repo)
+    })
+    .await?;
+
+    if let Ok(json) = serde_json::to_string(&result) {
+        state.git_cache.insert(key, json);
+    }
+    Ok(result)
 }
 
 #[tauri::command]
-pub fn git_stage_file(path: String, state: State<'_, AppState>) -> Result<(), String> {
+pub fn git_stage_file(path: String, state: State<'_, AppState>) -> Result<(), GitError> {
     let vault = state.vault_path.lock().unwrap();
-    let vault_path = vault.as_ref().ok_or("No vault open")?;
+    let vault_path = vault.as_ref().ok_or_else(GitError::no_vault)?;
 
-    let repo = git_service::open_repo(vault_path).map_err(|e| e.to_string())?;
-    git_service::stage_file(&repo, &path).map_err(|e| e.to_string())
+    let repo = git_service::open_repo(vault_path)?;
+    git_service::stage_file(&repo, &path)?;
+    state.git_cache.invalidate_all();
+    Ok(())
 }
 
 #[tauri::command]
-pub fn git_unstage_file(path: String, state: State<'_, AppState>) -> Result<(), String> {
+pub fn git_unstage_file(path: String, state: State<'_, AppState>) -> Result<(), GitError> {
     let vault = state.vault_path.lock().unwrap();
-    let vault_path = vault.as_ref().ok_or("No vault open")?;
+    let vault_path = vault.as_ref().ok_or_else(GitError::no_vault)?;
 
-    let repo = git_service::open_repo(vault_path).map_err(|e| e.to_string())?;
-    git_service::unstage_file(&repo, &path).map_err(|e| e.to_string())
+    let repo = git_service::open_repo(vault_path)?;
+    git_service::unstage_file(&repo, &path)?;
+    state.git_cache.invalidate_all();
+    Ok(())
 }
 
 #[tauri::command]
-pub fn git_stage_all(state: State<'_, AppState>) -> Result<(), String> {
+pub fn git_stage_hunk(
+    path: String,
+    hunk_index: usize,
+    state: State<'_, AppState>,
+) -> Result<(), GitError> {
     let vault = state.vault_path.lock().unwrap();
-    let vault_path = vault.as_ref().ok_or("No vault open")?;
+    let vault_path = vault.as_ref().ok_or_else(GitError::no_vault)?;
 
-    let repo = git_service::open_repo(vault_path).map_err(|e| e.to_string())?;
-    git_service::stage_all(&repo).map_err(|e| e.to_string())
+    let repo = git_service::open_repo(vault_path)?;
+    git_service::stage_hunk(&repo, &path, hunk_index)?;
+    state.git_cache.invalidate_all();
+    Ok(())
 }
 
 #[tauri::command]
-pub fn git_commit(message: String, state: State<'_, AppState>) -> Result<String, String> {
+pub fn git_stage_lines(
+    path: String,
+    hunk: DiffHunk,
+    selected_lines: Vec<usize>,
+    state: State<'_, AppState>,
+) -> Result<(), GitError> {
     let vault = state.vault_path.lock().unwrap();
-    let vault_path = vault.as_ref().ok_or("No vault open")?;
+    let vault_path = vault.as_ref().ok_or_else(GitError::no_vault)?;
 
-    let repo = git_service::open_repo(vault_path).map_err(|e| e.to_string())?;
-    git_service::commit(&repo, &message).map_err(|e| e.to_string())
+    let repo = git_service::open_repo(vault_path)?;
+    let selected_lines = selected_lines.into_iter().collect();
+    git_service::stage_lines(&repo, &path, &hunk, &selected_lines)?;
+    state.git_cache.invalidate_all();
+    Ok(())
 }
 
 #[tauri::command]
-pub fn git_current_branch(state: State<'_, AppState>) -> Result<String, String> {
+pub fn git_stage_all(state: State<'_, AppState>) -> Result<(), GitError> {
     let vault = state.vault_path.lock().unwrap();
-    let vault_path = vault.as_ref().ok_or("No vault open")?;
+    let vault_path = vault.as_ref().ok_or_else(GitError::no_vault)?;
 
-    let repo = git_service::open_repo(vault_path).map_err(|e| e.to_string())?;
-    git_service::current_branch(&repo).map_err(|e| e.to_string())
+    let repo = git_service::open_repo(vault_path)?;
+    git_service::stage_all(&repo)?;
+    state.git_cache.invalidate_all();
+    Ok(())
 }
 
 #[tauri::command]
-pub fn git_branches(state: State<'_, AppState>) -> Result<Vec<BranchInfo>, String> {
+pub fn git_commit(message: String, state: State<'_, AppState>) -> Result<String, GitError> {
     let vault = state.vault_path.lock().unwrap();
-    let vault_path = vault.as_ref().ok_or("No vault open")?;
+    let vault_path = vault.as_ref().ok_or_else(GitError::no_vault)?;
 
-    let repo = git_service::open_repo(vault_path).map_err(|e| e.to_string())?;
-    git_service::list_branches(&repo).map_err(|e| e.to_string())
+    let repo = git_service::open_repo(vault_path)?;
+    let oid = git_service::commit(&repo, &message)?;
+    state.git_cache.invalidate_all();
+    Ok(oid)
 }
 
 #[tauri::command]
-pub fn git_create_branch(name: String, state: State<'_, AppState>) -> Result<(), String> {
+pub fn git_current_branch(state: State<'_, AppState>) -> Result<String, GitError> {
     let vault = state.vault_path.lock().unwrap();
-    let vault_path = vault.as_ref().ok_or("No vault open")?;
+    let vault_path = vault.as_ref().ok_or_else(GitError::no_vault)?;
+
+    let repo = git_service::open_repo(vault_path)?;
+    git_service::current_branch(&repo)
+}
 
-    let repo = git_service::open_repo(vault_path).map_err(|e| e.to_string())?;
-    git_service::create_branch(&repo, &name).map_err(|e| e.to_string())
+#[tauri::command]
+pub async fn git_branches(state: State<'_, AppState>) -> Result<Vec<BranchInfo>, GitError> {
+    let vault_path = state
+        .vault_path
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or_else(GitError::no_vault)?;
+    let key = cache_key(&vault_path, "branches", "");
+
+    if let Some(cached) = state.git_cache.get(&key) {
+        return from_cache(&cached);
+    }
+
+    let result = run_blocking(move || {
+        let repo = git_service::open_repo(&vault_path)?;
+        git_service::list_branches(&repo)
+    })
+    .await?;
+
+    if let Ok(json) = serde_json::to_string(&result) {
+        state.git_cache.insert(key, json);
+    }
+    Ok(result)
 }
 
 #[tauri::command]
-pub fn git_checkout_branch(name: String, state: State<'_, AppState>) -> Result<(), String> {
+pub fn git_create_branch(name: String, state: State<'_, AppState>) -> Result<(), GitError> {
     let vault = state.vault_path.lock().unwrap();
-    let vault_path = vault.as_ref().ok_or("No vault open")?;
+    let vault_path = vault.as_ref().ok_or_else(GitError::no_vault)?;
 
-    let repo = git_service::open_repo(vault_path).map_err(|e| e.to_string())?;
-    git_service::checkout_branch(&repo, &name).map_err(|e| e.to_string())
+    let repo = git_service::open_repo(vault_path)?;
+    git_service::create_branch(&repo, &name)?;
+    state.git_cache.invalidate_all();
+    Ok(())
 }
 
 #[tauri::command]
-pub fn git_log(max_count: Option<usize>, state: State<'_, AppState>) -> Result<Vec<CommitInfo>, String> {
+pub fn git_checkout_branch(name: String, state: State<'_, AppState>) -> Result<(), GitError> {
     let vault = state.vault_path.lock().unwrap();
-    let vault_path = vault.as_ref().ok_or("No vault open")?;
+    let vault_path = vault.as_ref().ok_or_else(GitError::no_vault)?;
 
-    let repo = git_service::open_repo(vault_path).map_err(|e| e.to_string())?;
-    git_service::get_log(&repo, max_count.unwrap_or(50)).map_err(|e| e.to_string())
+    let repo = git_service::open_repo(vault_path)?;
+    git_service::checkout_branch(&repo, &name)?;
+    state.git_cache.invalidate_all();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn git_log(
+    max_count: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<CommitInfo>, GitError> {
+    let vault_path = state
+        .vault_path
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or_else(GitError::no_vault)?;
+    let max_count = max_count.unwrap_or(50);
+    let key = cache_key(&vault_path, "log", &max_count.to_string());
+
+    if let Some(cached) = state.git_cache.get(&key) {
+        return from_cache(&cached);
+    }
+
+    let result = run_blocking(move || {
+        let repo = git_service::open_repo(&vault_path)?;
+        git_service::get_log(&repo, max_count)
+    })
+    .await?;
+
+    if let Ok(json) = serde_json::to_string(&result) {
+        state.git_cache.insert(key, json);
+    }
+    Ok(result)
 }
 
 #[tauri::command]
@@ -99,13 +260,26 @@ pub fn git_file_log(
     file_path: String,
     max_count: Option<usize>,
     state: State<'_, AppState>,
-) -> Result<Vec<CommitInfo>, String> {
+) -> Result<Vec<CommitInfo>, GitError> {
     let vault = state.vault_path.lock().unwrap();
-    let vault_path = vault.as_ref().ok_or("No vault open")?;
+    let vault_path = vault.as_ref().ok_or_else(GitError::no_vault)?;
 
-    let repo = git_service::open_repo(vault_path).map_err(|e| e.to_string())?;
+    let repo = git_service::open_repo(vault_path)?;
     git_service::get_file_log(&repo, &file_path, max_count.unwrap_or(50))
-        .map_err(|e| e.to_string())
+}
+
+/// Verify a commit's embedded signature against the configured signer
+/// (`gpg.format`), returning `Unsigned` rather than erroring if it has none.
+#[tauri::command]
+pub fn git_verify_commit(
+    commit_id: String,
+    state: State<'_, AppState>,
+) -> Result<VerificationStatus, GitError> {
+    let vault = state.vault_path.lock().unwrap();
+    let vault_path = vault.as_ref().ok_or_else(GitError::no_vault)?;
+
+    let repo = git_service::open_repo(vault_path)?;
+    git_service::verify_commit(&repo, &commit_id)
 }
 
 #[tauri::command]
@@ -113,46 +287,330 @@ pub fn git_file_at_commit(
     commit_id: String,
     file_path: String,
     state: State<'_, AppState>,
-) -> Result<String, String> {
+) -> Result<String, GitError> {
     let vault = state.vault_path.lock().unwrap();
-    let vault_path = vault.as_ref().ok_or("No vault open")?;
+    let vault_path = vault.as_ref().ok_or_else(GitError::no_vault)?;
 
-    let repo = git_service::open_repo(vault_path).map_err(|e| e.to_string())?;
-    git_service::get_file_at_commit(&repo, &commit_id, &file_path).map_err(|e| e.to_string())
+    let repo = git_service::open_repo(vault_path)?;
+    git_service::get_file_at_commit(&repo, &commit_id, &file_path)
 }
 
 #[tauri::command]
-pub fn git_diff(state: State<'_, AppState>) -> Result<Vec<FileDiff>, String> {
+pub fn git_blame(file_path: String, state: State<'_, AppState>) -> Result<Vec<BlameLine>, GitError> {
     let vault = state.vault_path.lock().unwrap();
-    let vault_path = vault.as_ref().ok_or("No vault open")?;
+    let vault_path = vault.as_ref().ok_or_else(GitError::no_vault)?;
 
-    let repo = git_service::open_repo(vault_path).map_err(|e| e.to_string())?;
-    git_service::get_diff(&repo).map_err(|e| e.to_string())
+    let repo = git_service::open_repo(vault_path)?;
+    git_service::get_blame(&repo, &file_path)
 }
 
 #[tauri::command]
-pub fn git_pull(state: State<'_, AppState>) -> Result<String, String> {
+pub async fn git_diff(
+    highlight: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<Vec<FileDiff>, GitError> {
+    let vault_path = state
+        .vault_path
+        .lock()
+        .unwrap()
+        .clone()
+        .ok_or_else(GitError::no_vault)?;
+    let highlight = highlight.unwrap_or(true);
+    let key = cache_key(&vault_path, "diff", &highlight.to_string());
+
+    if let Some(cached) = state.git_cache.get(&key) {
+        return from_cache(&cached);
+    }
+
+    let syntax_set = state.syntax_set.clone();
+    let result = run_blocking(move || {
+        let repo = git_service::open_repo(&vault_path)?;
+        git_service::get_diff(&repo, highlight.then_some(&syntax_set))
+    })
+    .await?;
+
+    if let Ok(json) = serde_json::to_string(&result) {
+        state.git_cache.insert(key, json);
+    }
+    Ok(result)
+}
+
+#[tauri::command]
+pub fn git_pull(app: AppHandle, state: State<'_, AppState>) -> Result<String, GitError> {
+    let vault = state.vault_path.lock().unwrap();
+    let vault_path = vault.as_ref().ok_or_else(GitError::no_vault)?;
+
+    let repo = git_service::open_repo(vault_path)?;
+    let result = git_service::pull(&repo, |progress| {
+        let _ = app.emit("vault://fetch-progress", progress);
+    })?;
+    state.git_cache.invalidate_all();
+    Ok(result)
+}
+
+#[tauri::command]
+pub fn git_push(app: AppHandle, state: State<'_, AppState>) -> Result<(), GitError> {
+    let vault = state.vault_path.lock().unwrap();
+    let vault_path = vault.as_ref().ok_or_else(GitError::no_vault)?;
+
+    let repo = git_service::open_repo(vault_path)?;
+    git_service::push(&repo, |progress| {
+        let _ = app.emit("vault://push-progress", progress);
+    })?;
+    state.git_cache.invalidate_all();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn git_stash_save(
+    message: String,
+    include_untracked: bool,
+    state: State<'_, AppState>,
+) -> Result<String, GitError> {
     let vault = state.vault_path.lock().unwrap();
-    let vault_path = vault.as_ref().ok_or("No vault open")?;
+    let vault_path = vault.as_ref().ok_or_else(GitError::no_vault)?;
 
-    let repo = git_service::open_repo(vault_path).map_err(|e| e.to_string())?;
-    git_service::pull(&repo).map_err(|e| e.to_string())
+    let mut repo = git_service::open_repo(vault_path)?;
+    let result = git_service::stash_save(&mut repo, &message, include_untracked)?;
+    state.git_cache.invalidate_all();
+    Ok(result)
 }
 
 #[tauri::command]
-pub fn git_push(state: State<'_, AppState>) -> Result<(), String> {
+pub fn git_stash_list(state: State<'_, AppState>) -> Result<Vec<StashEntry>, GitError> {
     let vault = state.vault_path.lock().unwrap();
-    let vault_path = vault.as_ref().ok_or("No vault open")?;
+    let vault_path = vault.as_ref().ok_or_else(GitError::no_vault)?;
 
-    let repo = git_service::open_repo(vault_path).map_err(|e| e.to_string())?;
-    git_service::push(&repo).map_err(|e| e.to_string())
+    let mut repo = git_service::open_repo(vault_path)?;
+    git_service::stash_list(&mut repo)
 }
 
 #[tauri::command]
-pub fn git_conflicts(state: State<'_, AppState>) -> Result<Vec<ConflictFile>, String> {
+pub fn git_stash_apply(
+    index: usize,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), GitError> {
     let vault = state.vault_path.lock().unwrap();
-    let vault_path = vault.as_ref().ok_or("No vault open")?;
+    let vault_path = vault.as_ref().ok_or_else(GitError::no_vault)?;
+
+    let mut repo = git_service::open_repo(vault_path)?;
+    git_service::stash_apply(&mut repo, index, |stage| {
+        let _ = app.emit("vault://stash-progress", stage);
+    })?;
+    state.git_cache.invalidate_all();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn git_stash_pop(
+    index: usize,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), GitError> {
+    let vault = state.vault_path.lock().unwrap();
+    let vault_path = vault.as_ref().ok_or_else(GitError::no_vault)?;
+
+    let mut repo = git_service::open_repo(vault_path)?;
+    git_service::stash_pop(&mut repo, index, |stage| {
+        let _ = app.emit("vault://stash-progress", stage);
+    })?;
+    state.git_cache.invalidate_all();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn git_stash_drop(index: usize, state: State<'_, AppState>) -> Result<(), GitError> {
+    let vault = state.vault_path.lock().unwrap();
+    let vault_path = vault.as_ref().ok_or_else(GitError::no_vault)?;
+
+    let mut repo = git_service::open_repo(vault_path)?;
+    git_service::stash_drop(&mut repo, index)?;
+    state.git_cache.invalidate_all();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn git_conflicts(state: State<'_, AppState>) -> Result<Vec<ConflictFile>, GitError> {
+    let vault = state.vault_path.lock().unwrap();
+    let vault_path = vault.as_ref().ok_or_else(GitError::no_vault)?;
+
+    let repo = git_service::open_repo(vault_path)?;
+    git_service::get_conflicts(&repo)
+}
+
+#[tauri::command]
+pub fn git_render_conflict(conflict: ConflictFile) -> Result<String, GitError> {
+    Ok(git_service::render_conflict(&conflict))
+}
+
+#[tauri::command]
+pub fn git_resolve_conflict(
+    path: String,
+    resolved_content: String,
+    state: State<'_, AppState>,
+) -> Result<(), GitError> {
+    let vault = state.vault_path.lock().unwrap();
+    let vault_path = vault.as_ref().ok_or_else(GitError::no_vault)?;
+
+    let repo = git_service::open_repo(vault_path)?;
+    git_service::resolve_conflict(&repo, &path, &resolved_content)?;
+    state.git_cache.invalidate_all();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn git_is_merge_in_progress(state: State<'_, AppState>) -> Result<bool, GitError> {
+    let vault = state.vault_path.lock().unwrap();
+    let vault_path = vault.as_ref().ok_or_else(GitError::no_vault)?;
+
+    let repo = git_service::open_repo(vault_path)?;
+    Ok(git_service::is_merge_in_progress(&repo))
+}
+
+#[tauri::command]
+pub fn git_abort_merge(state: State<'_, AppState>) -> Result<(), GitError> {
+    let vault = state.vault_path.lock().unwrap();
+    let vault_path = vault.as_ref().ok_or_else(GitError::no_vault)?;
+
+    let repo = git_service::open_repo(vault_path)?;
+    git_service::abort_merge(&repo)?;
+    state.git_cache.invalidate_all();
+    Ok(())
+}
+
+/// Read a git config value. `global` selects `Config::open_default()` over
+/// the vault's own `.git` config.
+#[tauri::command]
+pub fn git_get_config(
+    key: String,
+    global: bool,
+    state: State<'_, AppState>,
+) -> Result<Option<String>, GitError> {
+    let vault = state.vault_path.lock().unwrap();
+    let vault_path = vault.as_ref().ok_or_else(GitError::no_vault)?;
+
+    let repo = git_service::open_repo(vault_path)?;
+    git_service::get_config(&repo, &key, global)
+}
+
+/// Write a git config value, e.g. `user.name`, `user.email`, `commit.gpgsign`.
+#[tauri::command]
+pub fn git_set_config(
+    key: String,
+    value: String,
+    global: bool,
+    state: State<'_, AppState>,
+) -> Result<(), GitError> {
+    let vault = state.vault_path.lock().unwrap();
+    let vault_path = vault.as_ref().ok_or_else(GitError::no_vault)?;
+
+    let repo = git_service::open_repo(vault_path)?;
+    git_service::set_config(&repo, &key, &value, global)
+}
+
+/// Export a single commit as a standard `git format-patch` mbox blob, for
+/// sharing the change by mail or archiving it outside the vault's history.
+#[tauri::command]
+pub fn git_format_patch(commit_id: String, state: State<'_, AppState>) -> Result<String, GitError> {
+    let vault = state.vault_path.lock().unwrap();
+    let vault_path = vault.as_ref().ok_or_else(GitError::no_vault)?;
+
+    let repo = git_service::open_repo(vault_path)?;
+    git_service::format_patch(&repo, &commit_id)
+}
+
+/// Export every commit in `(from, to]` as its own numbered patch blob.
+#[tauri::command]
+pub fn git_format_patch_range(
+    from: String,
+    to: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, GitError> {
+    let vault = state.vault_path.lock().unwrap();
+    let vault_path = vault.as_ref().ok_or_else(GitError::no_vault)?;
+
+    let repo = git_service::open_repo(vault_path)?;
+    git_service::format_patch_range(&repo, &from, &to)
+}
+
+/// Export the current uncommitted diff (working tree + index) as a patch
+/// blob, for sharing or applying work that hasn't been committed yet.
+#[tauri::command]
+pub fn git_format_patch_working(state: State<'_, AppState>) -> Result<String, GitError> {
+    let vault = state.vault_path.lock().unwrap();
+    let vault_path = vault.as_ref().ok_or_else(GitError::no_vault)?;
+
+    let repo = git_service::open_repo(vault_path)?;
+    git_service::format_patch_working(&repo)
+}
 
-    let repo = git_service::open_repo(vault_path).map_err(|e| e.to_string())?;
-    git_service::get_conflicts(&repo).map_err(|e| e.to_string())
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use moka::sync::Cache;
+    use std::time::Duration;
+
+    #[test]
+    fn cache_key_is_unique_per_vault_op_and_args() {
+        let a = cache_key("/vault/one", "log", "50");
+        let b = cache_key("/vault/two", "log", "50");
+        let c = cache_key("/vault/one", "diff", "50");
+        let d = cache_key("/vault/one", "log", "100");
+
+        assert_ne!(a, b);
+        assert_ne!(a, c);
+        assert_ne!(a, d);
+        assert_eq!(a, cache_key("/vault/one", "log", "50"));
+    }
+
+    #[test]
+    fn from_cache_round_trips_and_rejects_garbage() {
+        let value: Vec<FileStatus> = vec![FileStatus {
+            path: "note.md".to_string(),
+            status: FileStatusKind::Modified,
+            staged: false,
+        }];
+        let json = serde_json::to_string(&value).unwrap();
+
+        let restored: Vec<FileStatus> = from_cache(&json).unwrap();
+        assert_eq!(restored.len(), 1);
+        assert_eq!(restored[0].path, "note.md");
+
+        let err = from_cache::<Vec<FileStatus>>("not json").unwrap_err();
+        assert_eq!(err.class, crate::models::git_error::ErrorClass::Io);
+    }
+
+    #[test]
+    fn cache_hit_then_invalidate_all_forces_a_miss() {
+        let cache: Cache<String, String> = Cache::builder()
+            .max_capacity(200)
+            .time_to_live(Duration::from_secs(8))
+            .build();
+
+        let key = cache_key("/vault", "status", "");
+        cache.insert(key.clone(), "[]".to_string());
+        assert_eq!(cache.get(&key), Some("[]".to_string()));
+
+        // A write command (or a watcher file-change event) invalidates
+        // every cached read, not just the one for the op that changed.
+        cache.invalidate_all();
+        cache.run_pending_tasks();
+        assert_eq!(cache.get(&key), None);
+    }
+
+    #[test]
+    fn cache_entry_expires_after_its_ttl() {
+        let cache: Cache<String, String> = Cache::builder()
+            .max_capacity(200)
+            .time_to_live(Duration::from_millis(20))
+            .build();
+
+        let key = cache_key("/vault", "branches", "");
+        cache.insert(key.clone(), "[]".to_string());
+        assert_eq!(cache.get(&key), Some("[]".to_string()));
+
+        std::thread::sleep(Duration::from_millis(100));
+        assert_eq!(cache.get(&key), None);
+    }
 }
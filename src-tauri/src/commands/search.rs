@@ -6,6 +6,7 @@ use crate::AppState;
 /// Search notes by query
 #[tauri::command]
 pub fn search_notes(query: String, state: State<'_, AppState>) -> Result<Vec<SearchResult>, String> {
-    let index = &state.search_index;
+    let index = state.search_index.lock().unwrap();
+    let index = index.as_ref().ok_or("No vault open")?;
     index.search(&query, 20).map_err(|e| e.to_string())
 }
@@ -1,16 +1,54 @@
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::Path;
-use tauri::State;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::UNIX_EPOCH;
+use tauri::{AppHandle, Emitter, State};
 use walkdir::WalkDir;
 
 use crate::models::link::GraphData;
 use crate::models::vault::VaultConfig;
+use crate::services::indexer::SearchIndex;
+use crate::services::watcher;
 use crate::services::{git_service, parser};
 use crate::AppState;
 
+/// Indexing progress, emitted to the frontend so it can show a spinner/count
+/// while a large vault is being (re)indexed.
+#[derive(Clone, serde::Serialize)]
+struct IndexProgress {
+    processed: usize,
+    total: usize,
+}
+
+/// A parsed note, ready to be applied to the search index and link graph.
+struct ParsedFile {
+    rel_path: String,
+    title: String,
+    body: String,
+    tags: Vec<String>,
+    links: Vec<String>,
+    modified: u64,
+}
+
+/// Modification time of a file on disk, in unix seconds (0 if unavailable).
+fn file_modified(path: &Path) -> u64 {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
 /// Open a vault (directory) and initialize all services
 #[tauri::command]
-pub fn open_vault(path: String, state: State<'_, AppState>) -> Result<VaultConfig, String> {
+pub fn open_vault(
+    path: String,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<VaultConfig, String> {
     let vault_path = Path::new(&path);
     if !vault_path.exists() {
         return Err("Directory does not exist".to_string());
@@ -28,8 +66,21 @@ pub fn open_vault(path: String, state: State<'_, AppState>) -> Result<VaultConfi
     // Store the vault path
     *state.vault_path.lock().unwrap() = Some(path.clone());
 
+    // Open (or create) the vault's persistent index. A warm open only
+    // re-indexes files whose on-disk mtime moved, so this is near-instant
+    // on subsequent opens of the same vault. The previous index is dropped
+    // first so its writer releases the lock on the index directory.
+    *state.search_index.lock().unwrap() = None;
+    let search_index = SearchIndex::open_or_create(&path).map_err(|e| e.to_string())?;
+    *state.search_index.lock().unwrap() = Some(search_index);
+
     // Index all markdown files
-    index_vault(&path, &state).map_err(|e| e.to_string())?;
+    index_vault(&path, &app, &state).map_err(|e| e.to_string())?;
+
+    // Replace the watcher: dropping the old handle tears down its watch on
+    // the previous vault before we start watching the new one.
+    let new_watcher = watcher::start_watcher(app, path.clone()).map_err(|e| e.to_string())?;
+    *state.watcher.lock().unwrap() = Some(new_watcher);
 
     Ok(VaultConfig {
         path,
@@ -66,6 +117,18 @@ pub fn resolve_wikilink(name: String, state: State<'_, AppState>) -> Result<Opti
     Ok(state.link_graph.resolve_link(&name))
 }
 
+/// Ranked `[[` autocomplete candidates for a partial note name/path
+#[tauri::command]
+pub fn complete_wikilink(
+    prefix: String,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, String> {
+    Ok(state
+        .link_graph
+        .complete_wikilink(&prefix, limit.unwrap_or(10)))
+}
+
 /// Get the full graph data
 #[tauri::command]
 pub fn get_graph_data(state: State<'_, AppState>) -> Result<GraphData, String> {
@@ -91,6 +154,7 @@ pub fn reindex_file(path: String, state: State<'_, AppState>) -> Result<(), Stri
     let full_path = Path::new(vault_path).join(&path);
     let content = fs::read_to_string(&full_path).map_err(|e| e.to_string())?;
     let parsed = parser::parse_note(&content);
+    let modified = file_modified(&full_path);
 
     let title = parsed
         .frontmatter
@@ -105,9 +169,10 @@ pub fn reindex_file(path: String, state: State<'_, AppState>) -> Result<(), Stri
         });
 
     // Update search index
-    state
-        .search_index
-        .index_note(&path, &title, &content, &parsed.tags)
+    let index = state.search_index.lock().unwrap();
+    let index = index.as_ref().ok_or("No vault open")?;
+    index
+        .index_note(&path, &title, &content, &parsed.tags, modified, &parsed.links)
         .map_err(|e| e.to_string())?;
 
     // Update link graph
@@ -117,32 +182,107 @@ pub fn reindex_file(path: String, state: State<'_, AppState>) -> Result<(), Stri
     Ok(())
 }
 
-/// Index all markdown files in the vault
-fn index_vault(vault_path: &str, state: &AppState) -> anyhow::Result<()> {
-    for entry in WalkDir::new(vault_path)
+/// Index all markdown files in the vault. On a warm open, a candidate whose
+/// on-disk mtime matches what's already stored in the index is skipped
+/// entirely — no read, no parse — and its link-graph entry is restored from
+/// the `links` field the Tantivy doc already carries. Only genuinely new or
+/// changed files pay for reading + parsing, which runs across a rayon
+/// parallel iterator (the slow part on a large vault); the results are then
+/// applied under the index's single writer and committed once at the end.
+fn index_vault(vault_path: &str, app: &AppHandle, state: &AppState) -> anyhow::Result<()> {
+    let index_guard = state.search_index.lock().unwrap();
+    let index = index_guard.as_ref().expect("search index opened above");
+
+    let stored_modified = index.modified_times()?;
+    let stored_links = index.stored_links()?;
+
+    let candidates: Vec<PathBuf> = WalkDir::new(vault_path)
         .into_iter()
         .filter_map(|e| e.ok())
-    {
-        let path = entry.path();
-
-        // Skip .git directory
-        if path
-            .components()
-            .any(|c| c.as_os_str() == ".git")
-        {
-            continue;
+        .map(|e| e.into_path())
+        .filter(|path| {
+            path.is_file()
+                && !path.components().any(|c| c.as_os_str() == ".git")
+                && matches!(
+                    path.extension().unwrap_or_default().to_string_lossy().as_ref(),
+                    "md" | "markdown"
+                )
+        })
+        .collect();
+
+    let seen_paths: HashSet<String> = candidates
+        .iter()
+        .map(|path| rel_path_of(path, vault_path))
+        .collect();
+
+    let total = candidates.len();
+    let parsed_files = parse_changed_files(vault_path, &candidates, &stored_modified, |done| {
+        if done % 20 == 0 || done == total {
+            let _ = app.emit("vault://index-progress", IndexProgress { processed: done, total });
         }
+    });
 
-        if path.is_file() {
-            let ext = path.extension().unwrap_or_default().to_string_lossy();
-            if ext == "md" || ext == "markdown" {
-                let rel_path = path
-                    .strip_prefix(vault_path)
-                    .unwrap_or(path)
-                    .to_string_lossy()
-                    .replace('\\', "/");
+    for parsed in &parsed_files {
+        let _ = index.index_note_batched(
+            &parsed.rel_path,
+            &parsed.title,
+            &parsed.body,
+            &parsed.tags,
+            parsed.modified,
+            &parsed.links,
+        );
+        state.link_graph.register_note_batch(&parsed.rel_path);
+        state
+            .link_graph
+            .update_links(&parsed.rel_path, parsed.links.clone());
+    }
+
+    // Unchanged candidates were never read, so restore their link-graph
+    // entry from what the index already had stored for them.
+    let reparsed: HashSet<&str> = parsed_files.iter().map(|p| p.rel_path.as_str()).collect();
+    for rel_path in seen_paths.iter().filter(|p| !reparsed.contains(p.as_str())) {
+        state.link_graph.register_note_batch(rel_path);
+        if let Some(links) = stored_links.get(rel_path) {
+            state.link_graph.update_links(rel_path, links.clone());
+        }
+    }
+
+    // Anything indexed previously that no longer exists on disk is gone.
+    for stale_path in stored_modified.keys().filter(|p| !seen_paths.contains(*p)) {
+        let _ = index.remove_note_batched(stale_path);
+        state.link_graph.remove_note_batch(stale_path);
+    }
 
-                if let Ok(content) = fs::read_to_string(path) {
+    // One trie rebuild for the whole batch, instead of one per note.
+    state.link_graph.rebuild_trie();
+
+    index.commit_batch()?;
+
+    Ok(())
+}
+
+/// Read + parse every candidate whose on-disk mtime doesn't match
+/// `stored_modified`, across a rayon parallel iterator — the slow part of
+/// `index_vault` on a large vault. `on_progress` is called once per
+/// candidate (changed or not) with the running count considered so far.
+fn parse_changed_files(
+    vault_path: &str,
+    candidates: &[PathBuf],
+    stored_modified: &HashMap<String, u64>,
+    on_progress: impl Fn(usize) + Sync,
+) -> Vec<ParsedFile> {
+    let processed = AtomicUsize::new(0);
+
+    candidates
+        .par_iter()
+        .filter_map(|path| {
+            let rel_path = rel_path_of(path, vault_path);
+            let modified = file_modified(path);
+            let result = if stored_modified.get(&rel_path) == Some(&modified) {
+                // Unchanged since the last index — nothing to read or parse.
+                None
+            } else {
+                fs::read_to_string(path).ok().map(|content| {
                     let parsed = parser::parse_note(&content);
                     let title = parsed
                         .frontmatter
@@ -155,16 +295,97 @@ fn index_vault(vault_path: &str, state: &AppState) -> anyhow::Result<()> {
                                 .to_string()
                         });
 
-                    let _ = state
-                        .search_index
-                        .index_note(&rel_path, &title, &content, &parsed.tags);
+                    ParsedFile {
+                        rel_path: rel_path.clone(),
+                        title,
+                        body: content,
+                        tags: parsed.tags,
+                        links: parsed.links,
+                        modified,
+                    }
+                })
+            };
 
-                    state.link_graph.register_note(&rel_path);
-                    state.link_graph.update_links(&rel_path, parsed.links);
-                }
-            }
-        }
+            on_progress(processed.fetch_add(1, Ordering::Relaxed) + 1);
+
+            result
+        })
+        .collect()
+}
+
+fn rel_path_of(path: &Path, vault_path: &str) -> String {
+    path.strip_prefix(vault_path)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize as AtomicCount;
+    use tempfile::TempDir;
+
+    fn write_note(dir: &TempDir, name: &str, content: &str) -> PathBuf {
+        let path = dir.path().join(name);
+        fs::write(&path, content).unwrap();
+        path
     }
 
-    Ok(())
+    #[test]
+    fn parse_changed_files_skips_files_with_a_matching_stored_mtime() {
+        let dir = TempDir::new().unwrap();
+        let unchanged = write_note(&dir, "unchanged.md", "# Unchanged\n");
+        let changed = write_note(
+            &dir,
+            "changed.md",
+            "---\ntitle: Changed Note\ntags: [a, b]\n---\n[[unchanged]]\n",
+        );
+
+        let mut stored_modified = HashMap::new();
+        stored_modified.insert(
+            rel_path_of(&unchanged, dir.path().to_str().unwrap()),
+            file_modified(&unchanged),
+        );
+        // `changed.md` has no stored entry, so it's treated as new.
+
+        let candidates = vec![unchanged, changed];
+        let calls = AtomicCount::new(0);
+        let parsed = parse_changed_files(
+            dir.path().to_str().unwrap(),
+            &candidates,
+            &stored_modified,
+            |_done| {
+                calls.fetch_add(1, Ordering::Relaxed);
+            },
+        );
+
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].rel_path, "changed.md");
+        assert_eq!(parsed[0].title, "Changed Note");
+        assert_eq!(parsed[0].tags, vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(parsed[0].links, vec!["unchanged".to_string()]);
+        // The progress callback still fires once per candidate, not once per
+        // *changed* candidate.
+        assert_eq!(calls.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn parse_changed_files_reparses_everything_when_nothing_is_stored() {
+        let dir = TempDir::new().unwrap();
+        let a = write_note(&dir, "a.md", "a\n");
+        let b = write_note(&dir, "b.md", "b\n");
+
+        let candidates = vec![a, b];
+        let parsed = parse_changed_files(
+            dir.path().to_str().unwrap(),
+            &candidates,
+            &HashMap::new(),
+            |_done| {},
+        );
+
+        let mut rel_paths: Vec<&str> = parsed.iter().map(|p| p.rel_path.as_str()).collect();
+        rel_paths.sort();
+        assert_eq!(rel_paths, vec!["a.md", "b.md"]);
+    }
 }
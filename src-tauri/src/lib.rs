@@ -2,23 +2,49 @@ mod commands;
 mod models;
 mod services;
 
+use moka::sync::Cache;
+use notify::RecommendedWatcher;
 use services::indexer::SearchIndex;
 use services::linker::LinkGraph;
 use std::sync::Mutex;
+use std::time::Duration;
+use syntect::highlighting::ThemeSet;
+use syntect::parsing::SyntaxSet;
 
 /// Shared application state accessible from all Tauri commands
 pub struct AppState {
     pub vault_path: Mutex<Option<String>>,
-    pub search_index: SearchIndex,
+    /// `None` until a vault is opened; re-pointed at a fresh persistent index
+    /// (under `<vault>/.commitpaper/index`) each time `open_vault` runs.
+    pub search_index: Mutex<Option<SearchIndex>>,
     pub link_graph: LinkGraph,
+    /// Handle of the filesystem watcher for the currently open vault.
+    /// Replacing it (on reopen) drops the previous one, which tears it down.
+    pub watcher: Mutex<Option<RecommendedWatcher>>,
+    /// Loaded once at startup; used to syntax-highlight diffs and code blocks.
+    pub syntax_set: SyntaxSet,
+    /// Loaded once at startup; selected by name when rendering code blocks.
+    pub theme_set: ThemeSet,
+    /// Serialized results of read-only git commands (status/log/diff/branches),
+    /// keyed by `"<vault_path>:<op>:<args>"`. Short TTL trades a little
+    /// staleness for not re-walking the repo on every poll; any write command
+    /// or vault file-change event invalidates it outright.
+    pub git_cache: Cache<String, String>,
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     let app_state = AppState {
         vault_path: Mutex::new(None),
-        search_index: SearchIndex::new().expect("Failed to create search index"),
+        search_index: Mutex::new(None),
         link_graph: LinkGraph::new(),
+        watcher: Mutex::new(None),
+        syntax_set: SyntaxSet::load_defaults_newlines(),
+        theme_set: ThemeSet::load_defaults(),
+        git_cache: Cache::builder()
+            .max_capacity(200)
+            .time_to_live(Duration::from_secs(8))
+            .build(),
     };
 
     tauri::Builder::default()
@@ -43,6 +69,7 @@ pub fn run() {
             commands::vault::get_backlinks,
             commands::vault::get_note_names,
             commands::vault::resolve_wikilink,
+            commands::vault::complete_wikilink,
             commands::vault::get_graph_data,
             commands::vault::get_local_graph,
             commands::vault::reindex_file,
@@ -55,12 +82,17 @@ pub fn run() {
             commands::files::create_folder,
             commands::files::get_file_tree,
             commands::files::save_binary_file,
+            commands::files::render_note_html,
             // Git commands
             commands::git::git_status,
+            commands::git::git_repo_summary,
             commands::git::git_stage_file,
             commands::git::git_unstage_file,
+            commands::git::git_stage_hunk,
+            commands::git::git_stage_lines,
             commands::git::git_stage_all,
             commands::git::git_commit,
+            commands::git::git_verify_commit,
             commands::git::git_current_branch,
             commands::git::git_branches,
             commands::git::git_create_branch,
@@ -68,10 +100,25 @@ pub fn run() {
             commands::git::git_log,
             commands::git::git_file_log,
             commands::git::git_file_at_commit,
+            commands::git::git_blame,
             commands::git::git_diff,
             commands::git::git_pull,
             commands::git::git_push,
+            commands::git::git_stash_save,
+            commands::git::git_stash_list,
+            commands::git::git_stash_apply,
+            commands::git::git_stash_pop,
+            commands::git::git_stash_drop,
             commands::git::git_conflicts,
+            commands::git::git_render_conflict,
+            commands::git::git_resolve_conflict,
+            commands::git::git_is_merge_in_progress,
+            commands::git::git_abort_merge,
+            commands::git::git_get_config,
+            commands::git::git_set_config,
+            commands::git::git_format_patch,
+            commands::git::git_format_patch_range,
+            commands::git::git_format_patch_working,
             // Search commands
             commands::search::search_notes,
         ])
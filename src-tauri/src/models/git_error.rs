@@ -0,0 +1,154 @@
+use serde::{Deserialize, Serialize};
+
+/// Broad category of a git command failure, so the frontend can branch on
+/// `class` (e.g. prompt for credentials, open the conflict resolver) instead
+/// of string-matching `message`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ErrorClass {
+    /// An unclassified `git2` failure; see `message`/`code` for detail.
+    Git2,
+    Io,
+    NoVault,
+    AuthRequired,
+    Conflict,
+    UpstreamMissing,
+}
+
+/// A structured git command failure, serialized to the frontend in place of
+/// a bare string so it can drive different UI per `class`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitError {
+    pub class: ErrorClass,
+    pub message: String,
+    /// The underlying `git2::ErrorCode` discriminant, when the failure came
+    /// from git2.
+    pub code: Option<i32>,
+}
+
+impl GitError {
+    pub fn no_vault() -> Self {
+        GitError {
+            class: ErrorClass::NoVault,
+            message: "No vault open".to_string(),
+            code: None,
+        }
+    }
+
+    /// Attach extra context to an existing error, `anyhow`-style, without
+    /// losing its `class`/`code`.
+    fn with_context(mut self, msg: &str) -> Self {
+        self.message = format!("{msg}: {}", self.message);
+        self
+    }
+}
+
+impl std::fmt::Display for GitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for GitError {}
+
+impl From<git2::Error> for GitError {
+    fn from(e: git2::Error) -> Self {
+        let message = e.message().to_string();
+        let class = match e.code() {
+            git2::ErrorCode::Auth | git2::ErrorCode::Certificate => ErrorClass::AuthRequired,
+            git2::ErrorCode::Conflict | git2::ErrorCode::Unmerged => ErrorClass::Conflict,
+            _ if message.to_lowercase().contains("upstream") => ErrorClass::UpstreamMissing,
+            _ => ErrorClass::Git2,
+        };
+
+        GitError {
+            class,
+            message,
+            code: Some(e.code() as i32),
+        }
+    }
+}
+
+impl From<std::io::Error> for GitError {
+    fn from(e: std::io::Error) -> Self {
+        GitError {
+            class: ErrorClass::Io,
+            message: e.to_string(),
+            code: None,
+        }
+    }
+}
+
+impl From<std::str::Utf8Error> for GitError {
+    fn from(e: std::str::Utf8Error) -> Self {
+        GitError {
+            class: ErrorClass::Io,
+            message: e.to_string(),
+            code: None,
+        }
+    }
+}
+
+/// Lets `git_service` attach a human-readable hint to a raw `git2::Error`
+/// with the same `.context("...")` call style `anyhow` uses elsewhere in
+/// this codebase, while still producing a classified `GitError`.
+pub trait GitResultExt<T> {
+    fn context(self, msg: &str) -> Result<T, GitError>;
+}
+
+impl<T> GitResultExt<T> for Result<T, git2::Error> {
+    fn context(self, msg: &str) -> Result<T, GitError> {
+        self.map_err(|e| GitError::from(e).with_context(msg))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::{ErrorClass as Git2ErrorClass, ErrorCode};
+
+    #[test]
+    fn classifies_auth_and_certificate_errors() {
+        let e = git2::Error::new(ErrorCode::Auth, Git2ErrorClass::Net, "auth failed");
+        assert_eq!(GitError::from(e).class, ErrorClass::AuthRequired);
+
+        let e = git2::Error::new(ErrorCode::Certificate, Git2ErrorClass::Net, "bad cert");
+        assert_eq!(GitError::from(e).class, ErrorClass::AuthRequired);
+    }
+
+    #[test]
+    fn classifies_conflict_and_unmerged_errors() {
+        let e = git2::Error::new(ErrorCode::Conflict, Git2ErrorClass::Checkout, "conflict");
+        assert_eq!(GitError::from(e).class, ErrorClass::Conflict);
+
+        let e = git2::Error::new(ErrorCode::Unmerged, Git2ErrorClass::Index, "unmerged");
+        assert_eq!(GitError::from(e).class, ErrorClass::Conflict);
+    }
+
+    #[test]
+    fn classifies_missing_upstream_by_message() {
+        let e = git2::Error::new(
+            ErrorCode::NotFound,
+            Git2ErrorClass::Reference,
+            "no upstream configured for branch 'main'",
+        );
+        assert_eq!(GitError::from(e).class, ErrorClass::UpstreamMissing);
+    }
+
+    #[test]
+    fn falls_back_to_generic_git2_class() {
+        let e = git2::Error::new(ErrorCode::GenericError, Git2ErrorClass::Os, "boom");
+        assert_eq!(GitError::from(e).class, ErrorClass::Git2);
+    }
+
+    #[test]
+    fn context_preserves_class_and_prefixes_message() {
+        let result: Result<(), git2::Error> = Err(git2::Error::new(
+            ErrorCode::NotFound,
+            Git2ErrorClass::Object,
+            "not found",
+        ));
+        let err = result.context("looking up commit").unwrap_err();
+        assert_eq!(err.class, ErrorClass::Git2);
+        assert_eq!(err.message, "looking up commit: not found");
+    }
+}
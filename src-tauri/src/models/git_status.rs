@@ -27,6 +27,10 @@ pub struct BranchInfo {
     pub upstream: Option<String>,
     pub ahead: u32,
     pub behind: u32,
+    /// Unix timestamp of the branch tip's commit, for recency sorting.
+    pub tip_timestamp: i64,
+    /// First line of the tip commit's message.
+    pub tip_summary: String,
 }
 
 /// A single commit entry
@@ -38,6 +42,21 @@ pub struct CommitInfo {
     pub author: String,
     pub email: String,
     pub timestamp: i64,
+    /// Whether the commit carries a `gpgsig` header. Checking this is cheap
+    /// (no external process), unlike actually verifying it — see
+    /// `verify_commit`/`git_verify_commit` for that.
+    pub signed: bool,
+}
+
+/// Result of checking a commit's embedded signature against the configured
+/// signer (`gpg` for `gpg.format = openpgp`, `ssh-keygen -Y verify` for
+/// `gpg.format = ssh`).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum VerificationStatus {
+    Good,
+    Bad,
+    Unknown,
+    Unsigned,
 }
 
 /// Diff information for a file
@@ -54,6 +73,11 @@ pub struct DiffHunk {
 pub struct DiffLine {
     pub content: String,
     pub origin: char,
+    /// Syntax-highlighted markup for `content`, spans classed per
+    /// `ClassStyle::SpacedPrefixed`. `None` when the caller opted out of
+    /// highlighting (e.g. for a huge diff) or the line failed to parse; a
+    /// file in an unknown language is highlighted as plain text.
+    pub html: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -62,6 +86,63 @@ pub struct FileDiff {
     pub hunks: Vec<DiffHunk>,
 }
 
+/// One-call status badge for the current branch: cheaper for the UI than
+/// deriving the same counts from `git_status`/`git_branches` client-side.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoSummary {
+    pub branch: Option<String>,
+    pub upstream: Option<String>,
+    pub ahead: u32,
+    pub behind: u32,
+    pub staged_count: usize,
+    pub unstaged_count: usize,
+    pub untracked_count: usize,
+    pub conflicted_count: usize,
+    pub stash_count: usize,
+}
+
+/// Fetch transfer stats, sampled from `git2::Progress` during `pull` so the
+/// UI can render a progress bar instead of a frozen spinner.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TransferProgress {
+    pub received_objects: usize,
+    pub total_objects: usize,
+    pub indexed_objects: usize,
+    pub received_bytes: usize,
+    pub local_objects: usize,
+}
+
+/// Push transfer stats, sampled from `RemoteCallbacks::push_transfer_progress`
+/// during `push`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PushTransferProgress {
+    pub current: usize,
+    pub total: usize,
+    pub bytes: usize,
+}
+
+/// Authorship of a single line in a file, as reported by `get_blame`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlameLine {
+    /// 1-based line number in the blamed version of the file (HEAD).
+    pub line_number: usize,
+    pub commit_id: String,
+    pub short_commit_id: String,
+    pub author: String,
+    pub timestamp: i64,
+    /// The line's number in the commit that introduced it, which can differ
+    /// from `line_number` once later commits add/remove lines above it.
+    pub orig_line_number: usize,
+}
+
+/// A single shelved change, as enumerated by `stash_list`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StashEntry {
+    pub index: usize,
+    pub message: String,
+    pub id: String,
+}
+
 /// Merge conflict info
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConflictFile {
@@ -13,6 +13,9 @@ pub struct VaultConfig {
 pub struct SearchResult {
     pub path: String,
     pub title: String,
+    /// Plain-text snippet, for contexts that can't render HTML.
     pub snippet: String,
+    /// Same snippet with matched query terms wrapped in `<mark>`.
+    pub snippet_html: String,
     pub score: f32,
 }
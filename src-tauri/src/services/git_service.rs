@@ -1,13 +1,23 @@
-use anyhow::{Context, Result};
 use git2::{
-    BranchType, Cred, DiffOptions, FetchOptions, IndexAddOption, MergeOptions,
-    PushOptions, RemoteCallbacks, Repository, Signature, StatusOptions, StatusShow,
+    ApplyLocation, ApplyOptions, BlameOptions, BranchType, Config, Cred, Diff, DiffOptions,
+    FetchOptions, IndexAddOption, MergeOptions, PushOptions, RemoteCallbacks, Repository,
+    StashApplyOptions, StashFlags, StatusOptions, StatusShow,
 };
 use std::cell::RefCell;
+use std::collections::HashSet;
 use std::path::Path;
+use syntect::html::{line_tokens_to_classed_spans, ClassStyle};
+use syntect::parsing::{ParseState, ScopeStack, SyntaxSet};
 
+use crate::models::git_error::GitResultExt as _;
+pub use crate::models::git_error::GitError;
 use crate::models::git_status::*;
 
+/// Alias so every function below reads the same as before the structured
+/// error type landed; the error half is now a classified `GitError`
+/// instead of an opaque `anyhow::Error`.
+pub type Result<T> = std::result::Result<T, GitError>;
+
 /// Open a git repository at the given path, or discover it from a subdirectory
 pub fn open_repo(path: &str) -> Result<Repository> {
     Repository::discover(path).context("Failed to find git repository")
@@ -121,15 +131,102 @@ pub fn stage_all(repo: &Repository) -> Result<()> {
     Ok(())
 }
 
-/// Create a commit with the staged changes
+/// Stage a single hunk of a file, leaving the rest of its changes unstaged.
+/// Builds the HEAD-to-workdir diff restricted to `path`, then re-applies
+/// only the hunk at `hunk_index` (in the order `get_diff` reports them) to
+/// the index.
+pub fn stage_hunk(repo: &Repository, path: &str, hunk_index: usize) -> Result<()> {
+    let head_tree = repo.head().ok().and_then(|h| h.peel_to_tree().ok());
+    let mut opts = DiffOptions::new();
+    opts.pathspec(path);
+
+    let diff = repo.diff_tree_to_workdir_with_index(head_tree.as_ref(), Some(&mut opts))?;
+
+    let seen = RefCell::new(0usize);
+    let mut apply_opts = ApplyOptions::new();
+    apply_opts.hunk_callback(|_hunk| {
+        let mut seen = seen.borrow_mut();
+        let is_selected = *seen == hunk_index;
+        *seen += 1;
+        is_selected
+    });
+
+    repo.apply(&diff, ApplyLocation::Index, Some(&mut apply_opts))?;
+    Ok(())
+}
+
+/// Stage only the selected lines of `hunk`. `selected_lines` holds indices
+/// into `hunk.lines` for the `+`/`-` lines to stage; every other added line
+/// is dropped from the synthesized patch and every other removed line is
+/// kept as context, so the patch still applies cleanly against the current
+/// index blob.
+pub fn stage_lines(
+    repo: &Repository,
+    path: &str,
+    hunk: &DiffHunk,
+    selected_lines: &HashSet<usize>,
+) -> Result<()> {
+    let patch = synthesize_hunk_patch(path, hunk, selected_lines);
+    let diff = Diff::from_buffer(patch.as_bytes())?;
+    repo.apply(&diff, ApplyLocation::Index, None)?;
+    Ok(())
+}
+
+/// Build a single-hunk unified diff for `path`, keeping unselected removed
+/// lines as context (since they're left untouched) and dropping unselected
+/// added lines entirely (since they never existed as far as this patch is
+/// concerned), then recomputing the hunk header's line counts to match.
+fn synthesize_hunk_patch(path: &str, hunk: &DiffHunk, selected_lines: &HashSet<usize>) -> String {
+    let mut body = String::new();
+    let mut old_lines = 0u32;
+    let mut new_lines = 0u32;
+
+    for (i, line) in hunk.lines.iter().enumerate() {
+        match line.origin {
+            '+' => {
+                if selected_lines.contains(&i) {
+                    body.push('+');
+                    body.push_str(&line.content);
+                    new_lines += 1;
+                }
+            }
+            '-' => {
+                if selected_lines.contains(&i) {
+                    body.push('-');
+                    body.push_str(&line.content);
+                    old_lines += 1;
+                } else {
+                    body.push(' ');
+                    body.push_str(&line.content);
+                    old_lines += 1;
+                    new_lines += 1;
+                }
+            }
+            _ => {
+                body.push(' ');
+                body.push_str(&line.content);
+                old_lines += 1;
+                new_lines += 1;
+            }
+        }
+    }
+
+    format!(
+        "diff --git a/{path} b/{path}\n--- a/{path}\n+++ b/{path}\n@@ -{},{} +{},{} @@\n{body}",
+        hunk.old_start, old_lines, hunk.new_start, new_lines
+    )
+}
+
+/// Create a commit with the staged changes. Signs it when `commit.gpgsign`
+/// is set, using whichever external signer `gpg.format` selects.
 pub fn commit(repo: &Repository, message: &str) -> Result<String> {
     let mut index = repo.index()?;
     let oid = index.write_tree()?;
     let tree = repo.find_tree(oid)?;
 
-    let sig = repo
-        .signature()
-        .unwrap_or_else(|_| Signature::now("Gitsidian User", "user@gitsidian").unwrap());
+    let sig = repo.signature().context(
+        "No git identity configured — set your name/email in Settings before committing",
+    )?;
 
     let parent = match repo.head() {
         Ok(head) => Some(head.peel_to_commit()?),
@@ -137,11 +234,238 @@ pub fn commit(repo: &Repository, message: &str) -> Result<String> {
     };
 
     let parents: Vec<&git2::Commit> = parent.iter().collect();
-    let commit_oid = repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)?;
+
+    let should_sign = get_config(repo, "commit.gpgsign", false)?
+        .map(|v| v == "true")
+        .unwrap_or(false);
+
+    if !should_sign {
+        let commit_oid = repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)?;
+        return Ok(commit_oid.to_string());
+    }
+
+    let buffer = repo.commit_create_buffer(&sig, &sig, message, &tree, &parents)?;
+    let buffer = buffer.as_str().ok_or_else(|| GitError {
+        class: crate::models::git_error::ErrorClass::Io,
+        message: "Commit buffer was not valid UTF-8".to_string(),
+        code: None,
+    })?;
+
+    let signature = sign_buffer(repo, buffer)?;
+    let commit_oid = repo.commit_signed(buffer, &signature, Some("gpgsig"))?;
+
+    // `commit_signed` only writes the object; the ref still has to be
+    // pointed at it ourselves, same as `repo.commit` would do internally.
+    let head_ref_name = match repo.head() {
+        Ok(head) => head.name().map(|s| s.to_string()),
+        Err(_) => repo
+            .find_reference("HEAD")
+            .ok()
+            .and_then(|h| h.symbolic_target().map(|s| s.to_string())),
+    };
+    let ref_name = head_ref_name.unwrap_or_else(|| "refs/heads/main".to_string());
+    repo.reference(&ref_name, commit_oid, true, message)?;
+    if parent.is_none() {
+        repo.set_head(&ref_name)?;
+    }
 
     Ok(commit_oid.to_string())
 }
 
+/// Which external tool signs/verifies commits, selected via the standard
+/// `gpg.format` config key (`openpgp`, the default, or `ssh`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SigningFormat {
+    OpenPgp,
+    Ssh,
+}
+
+fn signing_format(repo: &Repository) -> Result<SigningFormat> {
+    Ok(match get_config(repo, "gpg.format", false)?.as_deref() {
+        Some("ssh") => SigningFormat::Ssh,
+        _ => SigningFormat::OpenPgp,
+    })
+}
+
+/// Pipe a raw commit buffer to the configured external signer and return
+/// its detached signature, ready to be stored under the `gpgsig` header.
+fn sign_buffer(repo: &Repository, buffer: &str) -> Result<String> {
+    match signing_format(repo)? {
+        SigningFormat::OpenPgp => {
+            let program = get_config(repo, "gpg.program", false)?.unwrap_or_else(|| "gpg".to_string());
+            let mut args = vec!["--detach-sign".to_string(), "--armor".to_string()];
+            if let Some(key) = get_config(repo, "user.signingkey", false)? {
+                args.push("--local-user".to_string());
+                args.push(key);
+            }
+            run_piped(&program, &args, buffer.as_bytes())
+        }
+        SigningFormat::Ssh => {
+            let keyfile = get_config(repo, "user.signingkey", false)?.ok_or_else(|| GitError {
+                class: crate::models::git_error::ErrorClass::Git2,
+                message: "No user.signingkey configured for SSH commit signing".to_string(),
+                code: None,
+            })?;
+
+            // `ssh-keygen -Y sign` signs a file (not stdin) and writes the
+            // signature alongside it as `<file>.sig`.
+            let message_path = temp_path("commit");
+            std::fs::write(&message_path, buffer)?;
+            let sig_path = message_path.with_extension("sig");
+
+            let status = std::process::Command::new("ssh-keygen")
+                .args(["-Y", "sign", "-n", "git", "-f", &keyfile])
+                .arg(&message_path)
+                .status()?;
+
+            let result = if status.success() {
+                std::fs::read_to_string(&sig_path).map_err(GitError::from)
+            } else {
+                Err(GitError {
+                    class: crate::models::git_error::ErrorClass::Git2,
+                    message: format!("ssh-keygen -Y sign exited with {status}"),
+                    code: None,
+                })
+            };
+
+            let _ = std::fs::remove_file(&message_path);
+            let _ = std::fs::remove_file(&sig_path);
+            result
+        }
+    }
+}
+
+/// Verify a commit's embedded `gpgsig` against the configured signer.
+/// `Unsigned` if the commit carries no signature at all.
+pub fn verify_commit(repo: &Repository, commit_id: &str) -> Result<VerificationStatus> {
+    let oid = git2::Oid::from_str(commit_id)?;
+    let (signature, signed_data) = match repo.extract_signature(&oid, Some("gpgsig")) {
+        Ok(pair) => pair,
+        Err(_) => return Ok(VerificationStatus::Unsigned),
+    };
+
+    match signing_format(repo)? {
+        SigningFormat::OpenPgp => verify_gpg(repo, signature.as_ref(), signed_data.as_ref()),
+        SigningFormat::Ssh => verify_ssh(repo, signature.as_ref(), signed_data.as_ref()),
+    }
+}
+
+fn verify_gpg(repo: &Repository, signature: &[u8], signed_data: &[u8]) -> Result<VerificationStatus> {
+    let program = get_config(repo, "gpg.program", false)?.unwrap_or_else(|| "gpg".to_string());
+
+    let sig_path = temp_path("verify.asc");
+    let data_path = temp_path("verify.data");
+    std::fs::write(&sig_path, signature)?;
+    std::fs::write(&data_path, signed_data)?;
+
+    let output = std::process::Command::new(&program)
+        .arg("--verify")
+        .arg(&sig_path)
+        .arg(&data_path)
+        .output();
+
+    let _ = std::fs::remove_file(&sig_path);
+    let _ = std::fs::remove_file(&data_path);
+    let output = output?;
+
+    Ok(if output.status.success() {
+        VerificationStatus::Good
+    } else if String::from_utf8_lossy(&output.stderr).contains("BAD signature") {
+        VerificationStatus::Bad
+    } else {
+        VerificationStatus::Unknown
+    })
+}
+
+fn verify_ssh(repo: &Repository, signature: &[u8], signed_data: &[u8]) -> Result<VerificationStatus> {
+    // `ssh-keygen -Y verify` needs an allowed-signers file mapping
+    // principals to public keys; without one configured there's nothing to
+    // check the signature against.
+    let Some(allowed_signers) = get_config(repo, "gpg.ssh.allowedSignersFile", false)? else {
+        return Ok(VerificationStatus::Unknown);
+    };
+
+    let sig_path = temp_path("verify.sig");
+    let data_path = temp_path("verify.data");
+    std::fs::write(&sig_path, signature)?;
+    std::fs::write(&data_path, signed_data)?;
+
+    let output = std::process::Command::new("ssh-keygen")
+        .args(["-Y", "verify", "-f", &allowed_signers, "-I", "git", "-n", "git", "-s"])
+        .arg(&sig_path)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .and_then(|mut child| {
+            use std::io::Write;
+            child
+                .stdin
+                .take()
+                .expect("piped stdin")
+                .write_all(&std::fs::read(&data_path)?)?;
+            child.wait_with_output()
+        });
+
+    let _ = std::fs::remove_file(&sig_path);
+    let _ = std::fs::remove_file(&data_path);
+    let output = output?;
+
+    Ok(if output.status.success() {
+        VerificationStatus::Good
+    } else {
+        VerificationStatus::Bad
+    })
+}
+
+/// Monotonic suffix so concurrent `temp_path` calls in this process (e.g.
+/// batch-verifying several commits' signatures at once) never collide on
+/// the same scratch file.
+static TEMP_FILE_COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+/// A call-unique scratch file path under the OS temp dir for handing a
+/// signing/verification payload to an external CLI tool.
+fn temp_path(label: &str) -> std::path::PathBuf {
+    let unique = TEMP_FILE_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    std::env::temp_dir().join(format!(
+        "commitpaper-{label}-{}-{unique}",
+        std::process::id()
+    ))
+}
+
+fn run_piped(program: &str, args: &[String], input: &[u8]) -> Result<String> {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let mut child = std::process::Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    child
+        .stdin
+        .take()
+        .expect("piped stdin")
+        .write_all(input)?;
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(GitError {
+            class: crate::models::git_error::ErrorClass::Git2,
+            message: format!(
+                "{program} exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr)
+            ),
+            code: None,
+        });
+    }
+
+    String::from_utf8(output.stdout).map_err(GitError::from)
+}
+
 /// Get the current branch name
 pub fn current_branch(repo: &Repository) -> Result<String> {
     match repo.head() {
@@ -167,20 +491,100 @@ pub fn current_branch(repo: &Repository) -> Result<String> {
     }
 }
 
-/// List all local branches
+/// One-call status badge: current branch, upstream ahead/behind, file
+/// counts by category, and the stash count. Mirrors the summary computation
+/// prompt plugins like nushell's `gstat` do, so the frontend doesn't need to
+/// derive it from `get_status`/`list_branches` itself.
+pub fn repo_summary(repo: &mut Repository) -> Result<RepoSummary> {
+    let head = repo.head().ok();
+    let branch = head
+        .as_ref()
+        .filter(|h| h.is_branch())
+        .and_then(|h| h.shorthand())
+        .map(|s| s.to_string());
+
+    let (upstream, ahead, behind) = match branch.as_deref().and_then(|name| repo.find_branch(name, BranchType::Local).ok()) {
+        Some(local_branch) => {
+            let upstream_name = local_branch
+                .upstream()
+                .ok()
+                .and_then(|u| u.name().ok().flatten().map(|s| s.to_string()));
+
+            let counts = local_branch.get().target().and_then(|local_oid| {
+                local_branch
+                    .upstream()
+                    .ok()
+                    .and_then(|u| u.get().target())
+                    .and_then(|upstream_oid| repo.graph_ahead_behind(local_oid, upstream_oid).ok())
+            });
+            let (ahead, behind) = counts.unwrap_or((0, 0));
+            (upstream_name, ahead as u32, behind as u32)
+        }
+        None => (None, 0, 0),
+    };
+
+    let statuses = get_status(repo)?;
+    let staged_count = statuses.iter().filter(|f| f.staged).count();
+    let unstaged_count = statuses
+        .iter()
+        .filter(|f| {
+            !f.staged && matches!(f.status, FileStatusKind::Modified | FileStatusKind::Deleted)
+        })
+        .count();
+    let untracked_count = statuses
+        .iter()
+        .filter(|f| f.status == FileStatusKind::Untracked)
+        .count();
+    let conflicted_count = statuses
+        .iter()
+        .filter(|f| f.status == FileStatusKind::Conflicted)
+        .count();
+
+    let mut stash_count = 0usize;
+    let _ = repo.stash_foreach(|_, _, _| {
+        stash_count += 1;
+        true
+    });
+
+    Ok(RepoSummary {
+        branch,
+        upstream,
+        ahead,
+        behind,
+        staged_count,
+        unstaged_count,
+        untracked_count,
+        conflicted_count,
+        stash_count,
+    })
+}
+
+/// List all local branches, most-recently-committed tip first. A branch
+/// whose tip can't be resolved (e.g. it points at a missing object) is
+/// skipped rather than failing the whole call.
 pub fn list_branches(repo: &Repository) -> Result<Vec<BranchInfo>> {
     let mut branches = Vec::new();
 
     for branch in repo.branches(Some(BranchType::Local))? {
         let (branch, _) = branch?;
+
+        let Some(tip_oid) = branch.get().target() else {
+            continue;
+        };
+        let Ok(tip_commit) = repo.find_commit(tip_oid) else {
+            continue;
+        };
+
         let name = branch.name()?.unwrap_or("unknown").to_string();
         let is_head = branch.is_head();
 
         let (ahead, behind) = if let Ok(upstream) = branch.upstream() {
-            let local_oid = branch.get().target().unwrap();
-            let upstream_oid = upstream.get().target().unwrap();
-            repo.graph_ahead_behind(local_oid, upstream_oid)
-                .unwrap_or((0, 0))
+            match upstream.get().target() {
+                Some(upstream_oid) => repo
+                    .graph_ahead_behind(tip_oid, upstream_oid)
+                    .unwrap_or((0, 0)),
+                None => (0, 0),
+            }
         } else {
             (0, 0)
         };
@@ -190,15 +594,27 @@ pub fn list_branches(repo: &Repository) -> Result<Vec<BranchInfo>> {
             .ok()
             .and_then(|u| u.name().ok().flatten().map(|s| s.to_string()));
 
+        let tip_summary = tip_commit
+            .message()
+            .unwrap_or("")
+            .lines()
+            .next()
+            .unwrap_or("")
+            .to_string();
+
         branches.push(BranchInfo {
             name,
             is_head,
             upstream: upstream_name,
             ahead: ahead as u32,
             behind: behind as u32,
+            tip_timestamp: tip_commit.time().seconds(),
+            tip_summary,
         });
     }
 
+    branches.sort_by(|a, b| b.tip_timestamp.cmp(&a.tip_timestamp));
+
     Ok(branches)
 }
 
@@ -221,8 +637,33 @@ pub fn checkout_branch(repo: &Repository, name: &str) -> Result<()> {
     Ok(())
 }
 
+/// Whether a commit carries a `gpgsig` header. Just an existence check —
+/// see `verify_commit` for actually validating it against a signer.
+fn is_signed(repo: &Repository, oid: git2::Oid) -> bool {
+    repo.extract_signature(&oid, Some("gpgsig")).is_ok()
+}
+
+/// Resolve an author/committer signature through the repo's `.mailmap`
+/// (when one exists), so contributors who changed name/email over a
+/// project's history still resolve to one canonical identity. Falls back
+/// to the raw signature otherwise.
+fn resolve_author(mailmap: Option<&git2::Mailmap>, sig: &git2::Signature) -> (String, String) {
+    if let Some(resolved) = mailmap.and_then(|m| m.resolve_signature(sig).ok()) {
+        return (
+            resolved.name().unwrap_or("").to_string(),
+            resolved.email().unwrap_or("").to_string(),
+        );
+    }
+    (
+        sig.name().unwrap_or("").to_string(),
+        sig.email().unwrap_or("").to_string(),
+    )
+}
+
 /// Get commit log
 pub fn get_log(repo: &Repository, max_count: usize) -> Result<Vec<CommitInfo>> {
+    let mailmap = repo.mailmap().ok();
+
     let mut revwalk = repo.revwalk()?;
     match revwalk.push_head() {
         Ok(_) => {}
@@ -237,13 +678,15 @@ pub fn get_log(repo: &Repository, max_count: usize) -> Result<Vec<CommitInfo>> {
         }
         let oid = oid?;
         let commit = repo.find_commit(oid)?;
+        let (author, email) = resolve_author(mailmap.as_ref(), &commit.author());
         commits.push(CommitInfo {
             id: oid.to_string(),
             short_id: oid.to_string()[..7].to_string(),
             message: commit.message().unwrap_or("").to_string(),
-            author: commit.author().name().unwrap_or("").to_string(),
-            email: commit.author().email().unwrap_or("").to_string(),
+            author,
+            email,
             timestamp: commit.time().seconds(),
+            signed: is_signed(repo, oid),
         });
     }
 
@@ -252,6 +695,8 @@ pub fn get_log(repo: &Repository, max_count: usize) -> Result<Vec<CommitInfo>> {
 
 /// Get commit log for a specific file
 pub fn get_file_log(repo: &Repository, file_path: &str, max_count: usize) -> Result<Vec<CommitInfo>> {
+    let mailmap = repo.mailmap().ok();
+
     let mut revwalk = repo.revwalk()?;
     match revwalk.push_head() {
         Ok(_) => {}
@@ -277,13 +722,15 @@ pub fn get_file_log(repo: &Repository, file_path: &str, max_count: usize) -> Res
                 let blob_id = entry.id();
                 if last_blob_id.is_none() || last_blob_id != Some(blob_id) {
                     last_blob_id = Some(blob_id);
+                    let (author, email) = resolve_author(mailmap.as_ref(), &commit.author());
                     commits.push(CommitInfo {
                         id: oid.to_string(),
                         short_id: oid.to_string()[..7].to_string(),
                         message: commit.message().unwrap_or("").to_string(),
-                        author: commit.author().name().unwrap_or("").to_string(),
-                        email: commit.author().email().unwrap_or("").to_string(),
+                        author,
+                        email,
                         timestamp: commit.time().seconds(),
+                        signed: is_signed(repo, oid),
                     });
                 }
             }
@@ -291,13 +738,15 @@ pub fn get_file_log(repo: &Repository, file_path: &str, max_count: usize) -> Res
                 // File didn't exist at this point — if it existed before, it was deleted
                 if last_blob_id.is_some() {
                     last_blob_id = None;
+                    let (author, email) = resolve_author(mailmap.as_ref(), &commit.author());
                     commits.push(CommitInfo {
                         id: oid.to_string(),
                         short_id: oid.to_string()[..7].to_string(),
                         message: commit.message().unwrap_or("").to_string(),
-                        author: commit.author().name().unwrap_or("").to_string(),
-                        email: commit.author().email().unwrap_or("").to_string(),
+                        author,
+                        email,
                         timestamp: commit.time().seconds(),
+                        signed: is_signed(repo, oid),
                     });
                 }
             }
@@ -318,8 +767,45 @@ pub fn get_file_at_commit(repo: &Repository, commit_id: &str, file_path: &str) -
     Ok(content)
 }
 
-/// Get diff of working directory changes
-pub fn get_diff(repo: &Repository) -> Result<Vec<FileDiff>> {
+/// Per-line authorship for `file_path`, expanding each blame hunk into one
+/// `BlameLine` per line so a file viewer can show "who last changed this
+/// line" alongside `get_file_log`'s commit-level history.
+pub fn get_blame(repo: &Repository, file_path: &str) -> Result<Vec<BlameLine>> {
+    let mailmap = repo.mailmap().ok();
+
+    let mut opts = BlameOptions::new();
+    let blame = repo.blame_file(Path::new(file_path), Some(&mut opts))?;
+
+    let mut lines = Vec::new();
+    for hunk in blame.iter() {
+        let commit_id = hunk.orig_commit_id();
+        let signature = hunk.final_signature();
+        let (author, _) = resolve_author(mailmap.as_ref(), &signature);
+        let timestamp = signature.when().seconds();
+
+        let final_start = hunk.final_start_line();
+        let orig_start = hunk.orig_start_line();
+
+        for offset in 0..hunk.lines_in_hunk() {
+            lines.push(BlameLine {
+                line_number: final_start + offset,
+                commit_id: commit_id.to_string(),
+                short_commit_id: commit_id.to_string()[..7].to_string(),
+                author: author.clone(),
+                timestamp,
+                orig_line_number: orig_start + offset,
+            });
+        }
+    }
+
+    lines.sort_by_key(|l| l.line_number);
+    Ok(lines)
+}
+
+/// Get diff of working directory changes. When `syntax_set` is provided,
+/// each line is rendered to span-classed HTML in addition to its plain
+/// `content`; pass `None` to skip highlighting (e.g. for a huge diff).
+pub fn get_diff(repo: &Repository, syntax_set: Option<&SyntaxSet>) -> Result<Vec<FileDiff>> {
     let head_tree = repo.head().ok().and_then(|h| h.peel_to_tree().ok());
     let mut opts = DiffOptions::new();
 
@@ -364,6 +850,7 @@ pub fn get_diff(repo: &Repository) -> Result<Vec<FileDiff>> {
                             .unwrap_or("")
                             .to_string(),
                         origin: line.origin(),
+                        html: None,
                     });
                 }
             }
@@ -371,11 +858,52 @@ pub fn get_diff(repo: &Repository) -> Result<Vec<FileDiff>> {
         }),
     )?;
 
-    Ok(file_diffs.into_inner())
+    let mut file_diffs = file_diffs.into_inner();
+
+    if let Some(syntax_set) = syntax_set {
+        for file_diff in &mut file_diffs {
+            highlight_file_diff(syntax_set, file_diff);
+        }
+    }
+
+    Ok(file_diffs)
 }
 
-/// Pull from remote (fetch + merge)
-pub fn pull(repo: &Repository) -> Result<String> {
+/// Highlight every line of a `FileDiff` in place, keeping one `ParseState`
+/// per file so multi-line constructs (block comments, heredocs, ...) stay
+/// colored consistently across hunks instead of resetting each line.
+fn highlight_file_diff(syntax_set: &SyntaxSet, file_diff: &mut FileDiff) {
+    let syntax = syntax_set
+        .find_syntax_for_file(&file_diff.path)
+        .ok()
+        .flatten()
+        .unwrap_or_else(|| syntax_set.find_syntax_plain_text());
+
+    let mut parse_state = ParseState::new(syntax);
+    let mut scope_stack = ScopeStack::new();
+
+    for hunk in &mut file_diff.hunks {
+        for line in &mut hunk.lines {
+            line.html = parse_state
+                .parse_line(&line.content, syntax_set)
+                .ok()
+                .and_then(|ops| {
+                    line_tokens_to_classed_spans(
+                        &line.content,
+                        ops.as_slice(),
+                        ClassStyle::SpacedPrefixed,
+                        &mut scope_stack,
+                    )
+                    .ok()
+                });
+        }
+    }
+}
+
+/// Pull from remote (fetch + merge). `on_progress` is sampled throughout the
+/// network transfer so the caller can render a progress bar; annotated tags
+/// arrive along with the fetch.
+pub fn pull(repo: &Repository, mut on_progress: impl FnMut(TransferProgress)) -> Result<String> {
     let mut remote = repo.find_remote("origin")?;
     let branch_name = current_branch(repo)?;
 
@@ -387,37 +915,57 @@ pub fn pull(repo: &Repository) -> Result<String> {
             Cred::default()
         }
     });
+    callbacks.transfer_progress(|progress| {
+        on_progress(TransferProgress {
+            received_objects: progress.received_objects(),
+            total_objects: progress.total_objects(),
+            indexed_objects: progress.indexed_objects(),
+            received_bytes: progress.received_bytes(),
+            local_objects: progress.local_objects(),
+        });
+        true
+    });
 
     let mut fetch_opts = FetchOptions::new();
     fetch_opts.remote_callbacks(callbacks);
+    fetch_opts.download_tags(git2::AutotagOption::All);
 
     remote.fetch(&[&branch_name], Some(&mut fetch_opts), None)?;
 
+    let reused_objects = remote.stats().local_objects();
+
     // Merge
     let fetch_head = repo.find_reference("FETCH_HEAD")?;
     let fetch_commit = repo.reference_to_annotated_commit(&fetch_head)?;
 
     let (analysis, _) = repo.merge_analysis(&[&fetch_commit])?;
 
-    if analysis.is_up_to_date() {
-        Ok("Already up to date".to_string())
+    let message = if analysis.is_up_to_date() {
+        "Already up to date".to_string()
     } else if analysis.is_fast_forward() {
         let mut reference = repo.find_reference(&format!("refs/heads/{}", branch_name))?;
         reference.set_target(fetch_commit.id(), "Fast-forward")?;
         repo.set_head(&format!("refs/heads/{}", branch_name))?;
         repo.checkout_head(Some(git2::build::CheckoutBuilder::default().force()))?;
-        Ok("Fast-forward merge".to_string())
+        "Fast-forward merge".to_string()
     } else if analysis.is_normal() {
         let mut merge_opts = MergeOptions::new();
         repo.merge(&[&fetch_commit], Some(&mut merge_opts), None)?;
-        Ok("Merge completed (may have conflicts)".to_string())
+        "Merge completed (may have conflicts)".to_string()
     } else {
-        Ok("Nothing to do".to_string())
-    }
+        "Nothing to do".to_string()
+    };
+
+    Ok(if reused_objects > 0 {
+        format!("{message} (reused {reused_objects} local object(s))")
+    } else {
+        message
+    })
 }
 
-/// Push to remote
-pub fn push(repo: &Repository) -> Result<()> {
+/// Push to remote. `on_progress` is sampled throughout the network transfer
+/// so the caller can render a progress bar.
+pub fn push(repo: &Repository, mut on_progress: impl FnMut(PushTransferProgress)) -> Result<()> {
     let mut remote = repo.find_remote("origin")?;
     let branch_name = current_branch(repo)?;
 
@@ -429,6 +977,9 @@ pub fn push(repo: &Repository) -> Result<()> {
             Cred::default()
         }
     });
+    callbacks.push_transfer_progress(move |current, total, bytes| {
+        on_progress(PushTransferProgress { current, total, bytes });
+    });
 
     let mut push_opts = PushOptions::new();
     push_opts.remote_callbacks(callbacks);
@@ -441,6 +992,74 @@ pub fn push(repo: &Repository) -> Result<()> {
     Ok(())
 }
 
+/// Shelve the current changes in a new stash, so `pull`/`checkout_branch`
+/// don't fail on a dirty tree. Equivalent to `git stash push`.
+pub fn stash_save(repo: &mut Repository, message: &str, include_untracked: bool) -> Result<String> {
+    let sig = repo
+        .signature()
+        .context("No git identity configured — set your name/email in Settings before stashing")?;
+
+    let flags = if include_untracked {
+        StashFlags::INCLUDE_UNTRACKED
+    } else {
+        StashFlags::DEFAULT
+    };
+
+    let oid = repo.stash_save2(&sig, Some(message), Some(flags))?;
+    Ok(oid.to_string())
+}
+
+/// List all stashes, in the order `stash_foreach` reports them (most recent
+/// first).
+pub fn stash_list(repo: &mut Repository) -> Result<Vec<StashEntry>> {
+    let mut entries = Vec::new();
+    repo.stash_foreach(|index, message, oid| {
+        entries.push(StashEntry {
+            index,
+            message: message.to_string(),
+            id: oid.to_string(),
+        });
+        true
+    })?;
+    Ok(entries)
+}
+
+/// Apply a stash without removing it from the stash list.
+pub fn stash_apply(
+    repo: &mut Repository,
+    index: usize,
+    mut on_progress: impl FnMut(&str),
+) -> Result<()> {
+    let mut opts = StashApplyOptions::new();
+    opts.progress_callback(|progress| {
+        on_progress(&format!("{progress:?}"));
+        true
+    });
+    repo.stash_apply(index, Some(&mut opts))?;
+    Ok(())
+}
+
+/// Apply a stash, then drop it from the stash list if it applied cleanly.
+pub fn stash_pop(
+    repo: &mut Repository,
+    index: usize,
+    mut on_progress: impl FnMut(&str),
+) -> Result<()> {
+    let mut opts = StashApplyOptions::new();
+    opts.progress_callback(|progress| {
+        on_progress(&format!("{progress:?}"));
+        true
+    });
+    repo.stash_pop(index, Some(&mut opts))?;
+    Ok(())
+}
+
+/// Drop a stash without applying it.
+pub fn stash_drop(repo: &mut Repository, index: usize) -> Result<()> {
+    repo.stash_drop(index)?;
+    Ok(())
+}
+
 /// Get list of conflicted files during a merge
 pub fn get_conflicts(repo: &Repository) -> Result<Vec<ConflictFile>> {
     let index = repo.index()?;
@@ -474,3 +1093,246 @@ pub fn get_conflicts(repo: &Repository) -> Result<Vec<ConflictFile>> {
 
     Ok(result)
 }
+
+/// Render a conflicted file as a standard diff3-marked text: `diffy::merge`
+/// auto-resolves any non-conflicting regions and wraps the rest in
+/// `<<<<<<< ours` / `|||||||` / `=======` / `>>>>>>> theirs` markers, so the
+/// UI can show (and let the user edit) the same merge markers `git merge`
+/// would have written to the working tree.
+pub fn render_conflict(conflict: &ConflictFile) -> String {
+    let base = conflict.ancestor.as_deref().unwrap_or("");
+    let ours = conflict.ours.as_deref().unwrap_or("");
+    let theirs = conflict.theirs.as_deref().unwrap_or("");
+
+    match diffy::merge(base, ours, theirs) {
+        Ok(merged) => merged,
+        Err(conflicted) => conflicted,
+    }
+}
+
+/// Resolve a conflicted file by writing the chosen content to the working
+/// tree and staging it, clearing all three conflict stages in the index.
+pub fn resolve_conflict(repo: &Repository, path: &str, resolved_content: &str) -> Result<()> {
+    let workdir = repo.workdir().ok_or_else(|| GitError {
+        class: crate::models::git_error::ErrorClass::Io,
+        message: "Repository has no working directory".to_string(),
+        code: None,
+    })?;
+    std::fs::write(workdir.join(path), resolved_content)?;
+
+    let mut index = repo.index()?;
+    index.remove_path(Path::new(path))?;
+    index.add_path(Path::new(path))?;
+    index.write()?;
+    Ok(())
+}
+
+/// Whether a merge is currently in progress (i.e. `pull` stopped at
+/// conflicts and is waiting on `resolve_conflict`/`abort_merge`).
+pub fn is_merge_in_progress(repo: &Repository) -> bool {
+    repo.state() == git2::RepositoryState::Merge
+}
+
+/// Abort an in-progress merge, resetting the working tree and index back to
+/// HEAD.
+pub fn abort_merge(repo: &Repository) -> Result<()> {
+    let head = repo.head()?.peel_to_commit()?;
+    repo.reset(head.as_object(), git2::ResetType::Hard, None)?;
+    repo.cleanup_state()?;
+    Ok(())
+}
+
+/// Which config file a `git_get_config`/`git_set_config` call reads or writes.
+fn config_for_scope(repo: &Repository, global: bool) -> Result<Config> {
+    if global {
+        Config::open_default().context("Failed to open global git config")
+    } else {
+        repo.config().context("Failed to open repo git config")
+    }
+}
+
+/// Read a string config value (e.g. `user.name`, `user.email`).
+pub fn get_config(repo: &Repository, key: &str, global: bool) -> Result<Option<String>> {
+    let config = config_for_scope(repo, global)?;
+    match config.get_string(key) {
+        Ok(value) => Ok(Some(value)),
+        Err(e) if e.code() == git2::ErrorCode::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Write a string config value (e.g. `user.name`, `user.email`, `commit.gpgsign`).
+pub fn set_config(repo: &Repository, key: &str, value: &str, global: bool) -> Result<()> {
+    let mut config = config_for_scope(repo, global)?;
+    config.set_str(key, value)?;
+    Ok(())
+}
+
+/// Render a single commit as a standard `git format-patch` mbox blob.
+pub fn format_patch(repo: &Repository, commit_id: &str) -> Result<String> {
+    format_patch_one(repo, commit_id, 1, 1)
+}
+
+/// Render a range of commits (oldest to newest, exclusive of `from`) as one
+/// `NNNN-subject.patch` blob per commit, numbered as a series.
+pub fn format_patch_range(repo: &Repository, from: &str, to: &str) -> Result<Vec<String>> {
+    let from_oid = git2::Oid::from_str(from)?;
+    let to_oid = git2::Oid::from_str(to)?;
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push(to_oid)?;
+    revwalk.hide(from_oid)?;
+    revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)?;
+
+    let commit_ids: Vec<String> = revwalk
+        .map(|oid| oid.map(|o| o.to_string()))
+        .collect::<std::result::Result<_, _>>()?;
+
+    let total = commit_ids.len();
+    commit_ids
+        .iter()
+        .enumerate()
+        .map(|(i, commit_id)| format_patch_one(repo, commit_id, i + 1, total))
+        .collect()
+}
+
+/// Render the current uncommitted diff (working tree + index, against HEAD)
+/// as a `git format-patch`-style mbox blob, so it can be shared or applied
+/// with `git am` before it's ever committed. There's no real commit id for
+/// the mbox `From <oid>` separator line, so a zero oid stands in for it.
+pub fn format_patch_working(repo: &Repository) -> Result<String> {
+    let head_tree = repo.head().ok().and_then(|h| h.peel_to_tree().ok());
+    let mut diff_opts = DiffOptions::new();
+    let diff = repo.diff_tree_to_workdir_with_index(head_tree.as_ref(), Some(&mut diff_opts))?;
+
+    let sig = repo
+        .signature()
+        .context("No git identity configured — set your name/email in Settings before exporting a patch")?;
+
+    let email = git2::Email::from_diff(
+        &diff,
+        1,
+        1,
+        &git2::Oid::zero(),
+        "Uncommitted changes",
+        "",
+        &sig,
+        &mut diff_opts,
+    )?;
+
+    Ok(String::from_utf8_lossy(&email).to_string())
+}
+
+fn format_patch_one(
+    repo: &Repository,
+    commit_id: &str,
+    patch_idx: usize,
+    patch_count: usize,
+) -> Result<String> {
+    let oid = git2::Oid::from_str(commit_id)?;
+    let commit = repo.find_commit(oid)?;
+    let tree = commit.tree()?;
+    let parent_tree = commit.parent(0).ok().and_then(|p| p.tree().ok());
+
+    let mut diff_opts = DiffOptions::new();
+    let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut diff_opts))?;
+
+    let message = commit.message().unwrap_or("");
+    let summary = message.lines().next().unwrap_or("");
+    let body = message
+        .splitn(2, '\n')
+        .nth(1)
+        .unwrap_or("")
+        .trim_start_matches('\n');
+
+    let email = git2::Email::from_diff(
+        &diff,
+        patch_idx,
+        patch_count,
+        &oid,
+        summary,
+        body,
+        &commit.author(),
+        &mut diff_opts,
+    )?;
+
+    Ok(String::from_utf8_lossy(&email).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// A throwaway repo with an identity configured, so `commit`/`signature`
+    /// calls don't fail for lack of `user.name`/`user.email`.
+    fn init_repo() -> (TempDir, Repository) {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        {
+            let mut config = repo.config().unwrap();
+            config.set_str("user.name", "Test User").unwrap();
+            config.set_str("user.email", "test@example.com").unwrap();
+        }
+        (dir, repo)
+    }
+
+    #[test]
+    fn repo_summary_counts_every_status_bucket() {
+        let (dir, mut repo) = init_repo();
+
+        std::fs::write(dir.path().join("committed.md"), "original\n").unwrap();
+        stage_file(&repo, "committed.md").unwrap();
+        let oid = commit(&repo, "initial commit").unwrap();
+        assert!(!oid.is_empty());
+
+        // Unstaged modification to the committed file.
+        std::fs::write(dir.path().join("committed.md"), "changed\n").unwrap();
+        // Untracked new file.
+        std::fs::write(dir.path().join("untracked.md"), "new\n").unwrap();
+        // Staged new file.
+        std::fs::write(dir.path().join("staged.md"), "staged\n").unwrap();
+        stage_file(&repo, "staged.md").unwrap();
+
+        let summary = repo_summary(&mut repo).unwrap();
+        assert_eq!(summary.staged_count, 1);
+        assert_eq!(summary.unstaged_count, 1);
+        assert_eq!(summary.untracked_count, 1);
+        assert_eq!(summary.conflicted_count, 0);
+    }
+
+    #[test]
+    fn repo_summary_counts_unstaged_deletions() {
+        let (dir, mut repo) = init_repo();
+
+        std::fs::write(dir.path().join("a.md"), "a\n").unwrap();
+        std::fs::write(dir.path().join("b.md"), "b\n").unwrap();
+        stage_all(&repo).unwrap();
+        commit(&repo, "initial commit").unwrap();
+
+        std::fs::remove_file(dir.path().join("b.md")).unwrap();
+
+        let summary = repo_summary(&mut repo).unwrap();
+        assert_eq!(summary.unstaged_count, 1);
+        assert_eq!(summary.staged_count, 0);
+    }
+
+    #[test]
+    fn render_conflict_wraps_only_the_conflicting_region() {
+        let conflict = ConflictFile {
+            path: "note.md".to_string(),
+            ancestor: Some("line one\nshared\nline three\n".to_string()),
+            ours: Some("our change\nshared\nline three\n".to_string()),
+            theirs: Some("their change\nshared\nline three\n".to_string()),
+        };
+
+        let rendered = render_conflict(&conflict);
+        assert!(rendered.contains("<<<<<<<"));
+        assert!(rendered.contains("======="));
+        assert!(rendered.contains(">>>>>>>"));
+        assert!(rendered.contains("our change"));
+        assert!(rendered.contains("their change"));
+        // The unconflicted tail is preserved verbatim, not duplicated inside markers.
+        assert!(rendered.contains("shared\nline three"));
+    }
+}
@@ -1,9 +1,13 @@
 use anyhow::Result;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
 use tantivy::collector::TopDocs;
+use tantivy::directory::MmapDirectory;
 use tantivy::query::QueryParser;
 use tantivy::schema::*;
+use tantivy::snippet::{Snippet, SnippetGenerator};
 use tantivy::{doc, Index, IndexWriter, ReloadPolicy};
-use std::sync::Mutex;
 
 use crate::models::vault::SearchResult;
 
@@ -15,19 +19,61 @@ pub struct SearchIndex {
     title_field: Field,
     body_field: Field,
     tags_field: Field,
+    modified_field: Field,
+    links_field: Field,
 }
 
 impl SearchIndex {
     /// Create an in-memory search index
     pub fn new() -> Result<Self> {
+        let schema = Self::build_schema();
+        let index = Index::create_in_ram(schema.schema);
+        Self::from_index(index, schema.fields)
+    }
+
+    /// Open (or create) a persistent index under `<vault_path>/.commitpaper/index`,
+    /// backed by an `MmapDirectory` so a warm open reuses the on-disk segments.
+    pub fn open_or_create(vault_path: &str) -> Result<Self> {
+        let index_dir = Path::new(vault_path).join(".commitpaper").join("index");
+        std::fs::create_dir_all(&index_dir)?;
+
+        let schema = Self::build_schema();
+        let directory = MmapDirectory::open(&index_dir)?;
+        let index = Index::open_or_create(directory, schema.schema)?;
+        Self::from_index(index, schema.fields)
+    }
+
+    fn build_schema() -> SchemaWithFields {
         let mut schema_builder = Schema::builder();
         let path_field = schema_builder.add_text_field("path", STRING | STORED);
         let title_field = schema_builder.add_text_field("title", TEXT | STORED);
         let body_field = schema_builder.add_text_field("body", TEXT | STORED);
         let tags_field = schema_builder.add_text_field("tags", TEXT | STORED);
+        let modified_field = schema_builder.add_u64_field("modified", STORED);
+        // Stored only (not searchable): lets a warm `open_vault` restore an
+        // unchanged note's link-graph entry without re-reading/re-parsing
+        // its file. Joined on a control character, since `\x1f` can't
+        // appear in a wikilink target parsed out of markdown text.
+        let links_field = schema_builder.add_text_field("links", STORED);
         let schema = schema_builder.build();
 
-        let index = Index::create_in_ram(schema);
+        SchemaWithFields {
+            schema,
+            fields: (
+                path_field,
+                title_field,
+                body_field,
+                tags_field,
+                modified_field,
+                links_field,
+            ),
+        }
+    }
+
+    fn from_index(
+        index: Index,
+        (path_field, title_field, body_field, tags_field, modified_field, links_field): FieldTuple,
+    ) -> Result<Self> {
         let writer = index.writer(50_000_000)?; // 50MB heap
 
         Ok(Self {
@@ -37,25 +83,74 @@ impl SearchIndex {
             title_field,
             body_field,
             tags_field,
+            modified_field,
+            links_field,
         })
     }
 
-    /// Index a single note
-    pub fn index_note(&self, path: &str, title: &str, body: &str, tags: &[String]) -> Result<()> {
+    /// Index a single note, recording its on-disk `modified` timestamp (unix seconds)
+    /// and outgoing links. Commits immediately, so this is the path used for
+    /// one-off reindexes.
+    pub fn index_note(
+        &self,
+        path: &str,
+        title: &str,
+        body: &str,
+        tags: &[String],
+        modified: u64,
+        links: &[String],
+    ) -> Result<()> {
         let mut writer = self.writer.lock().unwrap();
+        self.add_note(&mut writer, path, title, body, tags, modified, links)?;
+        writer.commit()?;
+        Ok(())
+    }
 
+    /// Queue a note for indexing without committing. Used by a bulk `index_vault`
+    /// pass so many files can be added under a single writer lock, with one
+    /// `commit_batch` at the end instead of a commit per document.
+    pub fn index_note_batched(
+        &self,
+        path: &str,
+        title: &str,
+        body: &str,
+        tags: &[String],
+        modified: u64,
+        links: &[String],
+    ) -> Result<()> {
+        let mut writer = self.writer.lock().unwrap();
+        self.add_note(&mut writer, path, title, body, tags, modified, links)
+    }
+
+    fn add_note(
+        &self,
+        writer: &mut IndexWriter,
+        path: &str,
+        title: &str,
+        body: &str,
+        tags: &[String],
+        modified: u64,
+        links: &[String],
+    ) -> Result<()> {
         // Delete existing document with this path
         let path_term = tantivy::Term::from_field_text(self.path_field, path);
         writer.delete_term(path_term);
 
-        // Add the document
         writer.add_document(doc!(
             self.path_field => path,
             self.title_field => title,
             self.body_field => body,
             self.tags_field => tags.join(" "),
+            self.modified_field => modified,
+            self.links_field => links.join("\x1f"),
         ))?;
 
+        Ok(())
+    }
+
+    /// Commit whatever has been queued via `index_note_batched`/`remove_note_batched`.
+    pub fn commit_batch(&self) -> Result<()> {
+        let mut writer = self.writer.lock().unwrap();
         writer.commit()?;
         Ok(())
     }
@@ -69,6 +164,92 @@ impl SearchIndex {
         Ok(())
     }
 
+    /// Queue a removal without committing (see `index_note_batched`).
+    pub fn remove_note_batched(&self, path: &str) -> Result<()> {
+        let mut writer = self.writer.lock().unwrap();
+        let path_term = tantivy::Term::from_field_text(self.path_field, path);
+        writer.delete_term(path_term);
+        Ok(())
+    }
+
+    /// Read the `path -> modified` map currently stored in the index, so a warm
+    /// `open_vault` can diff it against on-disk mtimes and skip unchanged files.
+    pub fn modified_times(&self) -> Result<HashMap<String, u64>> {
+        let reader = self
+            .index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        let searcher = reader.searcher();
+
+        let mut result = HashMap::new();
+        for segment_reader in searcher.segment_readers() {
+            let store = segment_reader.get_store_reader(0)?;
+            for doc_id in 0..segment_reader.max_doc() {
+                if segment_reader.is_deleted(doc_id) {
+                    continue;
+                }
+                let doc: TantivyDocument = store.get(doc_id)?;
+                let path = doc
+                    .get_first(self.path_field)
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .to_string();
+                let modified = doc
+                    .get_first(self.modified_field)
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(0);
+                if !path.is_empty() {
+                    result.insert(path, modified);
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Read the `path -> links` map currently stored in the index, so a warm
+    /// `open_vault` can restore an unchanged file's link-graph entry without
+    /// reading/parsing it again.
+    pub fn stored_links(&self) -> Result<HashMap<String, Vec<String>>> {
+        let reader = self
+            .index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        let searcher = reader.searcher();
+
+        let mut result = HashMap::new();
+        for segment_reader in searcher.segment_readers() {
+            let store = segment_reader.get_store_reader(0)?;
+            for doc_id in 0..segment_reader.max_doc() {
+                if segment_reader.is_deleted(doc_id) {
+                    continue;
+                }
+                let doc: TantivyDocument = store.get(doc_id)?;
+                let path = doc
+                    .get_first(self.path_field)
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .to_string();
+                let links = doc
+                    .get_first(self.links_field)
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("");
+                if !path.is_empty() {
+                    let links = if links.is_empty() {
+                        Vec::new()
+                    } else {
+                        links.split('\x1f').map(|s| s.to_string()).collect()
+                    };
+                    result.insert(path, links);
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
     /// Search the index
     pub fn search(&self, query_str: &str, max_results: usize) -> Result<Vec<SearchResult>> {
         let reader = self.index
@@ -85,6 +266,13 @@ impl SearchIndex {
         let query = query_parser.parse_query(query_str)?;
         let top_docs = searcher.search(&query, &TopDocs::with_limit(max_results))?;
 
+        // One generator per field: each knows how to pick the fragment of
+        // that field's text that best covers the matched terms.
+        let mut title_snippets = SnippetGenerator::create(&searcher, &*query, self.title_field)?;
+        let mut body_snippets = SnippetGenerator::create(&searcher, &*query, self.body_field)?;
+        title_snippets.set_max_num_chars(200);
+        body_snippets.set_max_num_chars(200);
+
         let mut results = Vec::new();
         for (score, doc_address) in top_docs {
             let doc: TantivyDocument = searcher.doc(doc_address)?;
@@ -104,17 +292,26 @@ impl SearchIndex {
                 .unwrap_or("")
                 .to_string();
 
-            // Create a snippet from body
-            let snippet = if body.len() > 200 {
-                format!("{}...", &body[..200])
+            // Prefer a body snippet (where the match is usually richer);
+            // fall back to the title snippet, then to a plain truncation
+            // on a char boundary when neither field actually matched.
+            let body_snippet = body_snippets.snippet(&body);
+            let title_snippet = title_snippets.snippet(&title);
+            let (snippet, snippet_html) = if !body_snippet.fragments().is_empty() {
+                (body_snippet.fragments().to_string(), marked_html(&body_snippet))
+            } else if !title_snippet.fragments().is_empty() {
+                (title_snippet.fragments().to_string(), marked_html(&title_snippet))
             } else {
-                body
+                let plain = truncate_on_char_boundary(&body, 200);
+                let html = escape_html(&plain);
+                (plain, html)
             };
 
             results.push(SearchResult {
                 path,
                 title,
                 snippet,
+                snippet_html,
                 score,
             });
         }
@@ -122,3 +319,47 @@ impl SearchIndex {
         Ok(results)
     }
 }
+
+/// Wrap a tantivy snippet's matched ranges in `<mark>…</mark>`, HTML-escaping
+/// everything else.
+fn marked_html(snippet: &Snippet) -> String {
+    let fragment = snippet.fragments();
+    let mut html = String::new();
+    let mut cursor = 0;
+
+    for highlight in snippet.highlighted() {
+        html.push_str(&escape_html(&fragment[cursor..highlight.start]));
+        html.push_str("<mark>");
+        html.push_str(&escape_html(&fragment[highlight.start..highlight.end]));
+        html.push_str("</mark>");
+        cursor = highlight.end;
+    }
+    html.push_str(&escape_html(&fragment[cursor..]));
+
+    html
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Truncate to at most `max_len` bytes without splitting a UTF-8 char.
+fn truncate_on_char_boundary(text: &str, max_len: usize) -> String {
+    if text.len() <= max_len {
+        return text.to_string();
+    }
+    let mut end = max_len;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}...", &text[..end])
+}
+
+type FieldTuple = (Field, Field, Field, Field, Field, Field);
+
+struct SchemaWithFields {
+    schema: Schema,
+    fields: FieldTuple,
+}
@@ -1,6 +1,7 @@
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use std::sync::RwLock;
+use trie_rs::{Trie, TrieBuilder};
 
 use crate::models::link::{GraphData, GraphEdge, GraphNode};
 
@@ -10,6 +11,13 @@ pub struct LinkGraph {
     outgoing: RwLock<HashMap<String, Vec<String>>>,
     /// Map from note name → note path (for resolving wikilinks)
     name_to_path: RwLock<HashMap<String, String>>,
+    /// Prefix trie over every note's name and relative path, for fast `[[`
+    /// autocomplete. Rebuilt from `name_to_path` on every register/remove,
+    /// since `trie_rs` tries are immutable once built and vaults are small
+    /// enough that this is cheap.
+    trie: RwLock<Trie<u8>>,
+    /// Trie key (name or path) → canonical note name.
+    trie_keys: RwLock<HashMap<String, String>>,
 }
 
 impl LinkGraph {
@@ -17,11 +25,35 @@ impl LinkGraph {
         Self {
             outgoing: RwLock::new(HashMap::new()),
             name_to_path: RwLock::new(HashMap::new()),
+            trie: RwLock::new(TrieBuilder::new().build()),
+            trie_keys: RwLock::new(HashMap::new()),
         }
     }
 
-    /// Register a note's path and name
-    pub fn register_note(&self, path: &str) {
+    /// Rebuild the trie (and its key → name map) from the current note set.
+    /// `register_note`/`remove_note` call this after every single change,
+    /// which is cheap for one-off edits (file watcher events, creating a
+    /// note); batch callers that touch many notes at once (`index_vault`)
+    /// should use the `_batch` variants below and call this once afterward,
+    /// rather than paying an O(N) rebuild per note.
+    pub fn rebuild_trie(&self) {
+        let name_to_path = self.name_to_path.read().unwrap();
+        let mut builder = TrieBuilder::new();
+        let mut keys = HashMap::new();
+
+        for (name, path) in name_to_path.iter() {
+            builder.push(name.clone());
+            keys.insert(name.clone(), name.clone());
+            builder.push(path.clone());
+            keys.insert(path.clone(), name.clone());
+        }
+
+        *self.trie.write().unwrap() = builder.build();
+        *self.trie_keys.write().unwrap() = keys;
+    }
+
+    /// Insert a note's path and name without rebuilding the trie.
+    fn insert_note(&self, path: &str) {
         let name = Path::new(path)
             .file_stem()
             .unwrap_or_default()
@@ -33,6 +65,18 @@ impl LinkGraph {
             .insert(name, path.to_string());
     }
 
+    /// Register a note's path and name
+    pub fn register_note(&self, path: &str) {
+        self.insert_note(path);
+        self.rebuild_trie();
+    }
+
+    /// Register a note without rebuilding the trie — for batch indexing
+    /// (see `index_vault`), which calls `rebuild_trie` once after the loop.
+    pub fn register_note_batch(&self, path: &str) {
+        self.insert_note(path);
+    }
+
     /// Update the links for a given note
     pub fn update_links(&self, source_path: &str, link_targets: Vec<String>) {
         self.outgoing
@@ -41,8 +85,8 @@ impl LinkGraph {
             .insert(source_path.to_string(), link_targets);
     }
 
-    /// Remove a note from the graph
-    pub fn remove_note(&self, path: &str) {
+    /// Remove a note's path and name without rebuilding the trie.
+    fn delete_note(&self, path: &str) {
         self.outgoing.write().unwrap().remove(path);
         let name = Path::new(path)
             .file_stem()
@@ -52,6 +96,65 @@ impl LinkGraph {
         self.name_to_path.write().unwrap().remove(&name);
     }
 
+    /// Remove a note from the graph
+    pub fn remove_note(&self, path: &str) {
+        self.delete_note(path);
+        self.rebuild_trie();
+    }
+
+    /// Remove a note without rebuilding the trie — for batch indexing (see
+    /// `index_vault`), which calls `rebuild_trie` once after the loop.
+    pub fn remove_note_batch(&self, path: &str) {
+        self.delete_note(path);
+    }
+
+    /// Count how many outgoing links across the vault point at `name`,
+    /// matching the way `get_graph_data` tallies `GraphNode::backlink_count`.
+    fn count_backlinks(&self, name: &str) -> usize {
+        self.outgoing
+            .read()
+            .unwrap()
+            .values()
+            .flatten()
+            .filter(|t| *t == name)
+            .count()
+    }
+
+    /// Ranked completions for the `[[` autocomplete popup: an exact stem
+    /// match sorts first, then prefix matches, tied broken by backlink
+    /// count so popular notes surface first. Replaces the old approach of
+    /// handing the frontend the full `get_all_note_names()` list to filter
+    /// client-side, which got noticeably slower to type against as a vault
+    /// grew past a few hundred notes.
+    pub fn complete_wikilink(&self, prefix: &str, limit: usize) -> Vec<String> {
+        let names: HashSet<String> = {
+            let trie = self.trie.read().unwrap();
+            let trie_keys = self.trie_keys.read().unwrap();
+            trie.predictive_search(prefix.as_bytes())
+                .into_iter()
+                .filter_map(|bytes: Vec<u8>| String::from_utf8(bytes).ok())
+                .filter_map(|key| trie_keys.get(&key).cloned())
+                .collect()
+        };
+
+        let mut ranked: Vec<(String, bool, usize)> = names
+            .into_iter()
+            .map(|name| {
+                let exact = name == prefix;
+                let backlinks = self.count_backlinks(&name);
+                (name, exact, backlinks)
+            })
+            .collect();
+
+        ranked.sort_by(|a, b| {
+            b.1.cmp(&a.1) // exact match first
+                .then(b.2.cmp(&a.2)) // then by backlink count, descending
+                .then(a.0.cmp(&b.0)) // tie-break alphabetically
+        });
+
+        ranked.into_iter().take(limit).map(|(name, ..)| name).collect()
+    }
+
     /// Get backlinks for a note (other notes that link to it)
     pub fn get_backlinks(&self, path: &str) -> Vec<String> {
         let name = Path::new(path)
@@ -220,3 +323,52 @@ impl LinkGraph {
         GraphData { nodes, edges }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn complete_wikilink_ranks_exact_match_first() {
+        let graph = LinkGraph::new();
+        graph.register_note("Rust.md");
+        graph.register_note("Rustacean.md");
+
+        let completions = graph.complete_wikilink("Rust", 10);
+        assert_eq!(completions.first(), Some(&"Rust".to_string()));
+        assert!(completions.contains(&"Rustacean".to_string()));
+    }
+
+    #[test]
+    fn complete_wikilink_breaks_ties_by_backlink_count() {
+        let graph = LinkGraph::new();
+        graph.register_note("Apple.md");
+        graph.register_note("Apricot.md");
+        graph.update_links("Other.md", vec!["Apricot".to_string()]);
+
+        let completions = graph.complete_wikilink("Ap", 10);
+        assert_eq!(completions, vec!["Apricot".to_string(), "Apple".to_string()]);
+    }
+
+    #[test]
+    fn batch_register_requires_explicit_rebuild() {
+        let graph = LinkGraph::new();
+        graph.register_note_batch("Batched.md");
+
+        // Not visible yet — the trie hasn't been rebuilt.
+        assert!(graph.complete_wikilink("Batch", 10).is_empty());
+
+        graph.rebuild_trie();
+        assert_eq!(graph.complete_wikilink("Batch", 10), vec!["Batched".to_string()]);
+    }
+
+    #[test]
+    fn remove_note_drops_it_from_autocomplete() {
+        let graph = LinkGraph::new();
+        graph.register_note("Temporary.md");
+        assert_eq!(graph.complete_wikilink("Temp", 10), vec!["Temporary".to_string()]);
+
+        graph.remove_note("Temporary.md");
+        assert!(graph.complete_wikilink("Temp", 10).is_empty());
+    }
+}
@@ -1,5 +1,12 @@
 use std::collections::HashMap;
-use comrak::Options;
+use std::io::Write;
+use comrak::adapters::SyntaxHighlighterAdapter;
+use comrak::{ComrakPlugins, Options};
+use syntect::easy::HighlightLines;
+use syntect::highlighting::Theme;
+use syntect::html::{styled_line_to_highlighted_html, IncludeBackground};
+use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
 
 /// Parse a markdown file and extract wikilinks and frontmatter
 pub fn parse_note(content: &str) -> ParsedNote {
@@ -131,8 +138,9 @@ fn extract_inline_tags(content: &str, tags: &mut Vec<String>) {
     }
 }
 
-/// Render markdown to HTML using comrak
-pub fn render_to_html(content: &str) -> String {
+/// Render markdown to HTML using comrak, with fenced code blocks
+/// syntax-highlighted via `syntect` against the given theme.
+pub fn render_to_html(content: &str, syntax_set: &SyntaxSet, theme: &Theme) -> String {
     let mut options = Options::default();
     options.extension.strikethrough = true;
     options.extension.table = true;
@@ -142,7 +150,59 @@ pub fn render_to_html(content: &str) -> String {
     options.extension.front_matter_delimiter = Some("---".to_string());
     options.render.unsafe_ = true;
 
-    comrak::markdown_to_html(content, &options)
+    let adapter = SyntectAdapter { syntax_set, theme };
+    let mut plugins = ComrakPlugins::default();
+    plugins.render.codefence_syntax_highlighter = Some(&adapter);
+
+    comrak::markdown_to_html_with_plugins(content, &options, &plugins)
+}
+
+/// Adapts `syntect` to comrak's `SyntaxHighlighterAdapter` so fenced code
+/// blocks are highlighted too. Unlike diffs (see `git_service`), which get
+/// CSS-classed spans, code blocks get inline styles from the chosen theme.
+struct SyntectAdapter<'a> {
+    syntax_set: &'a SyntaxSet,
+    theme: &'a Theme,
+}
+
+impl<'a> SyntaxHighlighterAdapter for SyntectAdapter<'a> {
+    fn write_highlighted(
+        &self,
+        output: &mut dyn Write,
+        lang: Option<&str>,
+        code: &str,
+    ) -> std::io::Result<()> {
+        let syntax = lang
+            .and_then(|lang| self.syntax_set.find_syntax_by_token(lang))
+            .unwrap_or_else(|| self.syntax_set.find_syntax_plain_text());
+
+        let mut highlighter = HighlightLines::new(syntax, self.theme);
+        for line in LinesWithEndings::from(code) {
+            let ranges = highlighter
+                .highlight_line(line, self.syntax_set)
+                .unwrap_or_default();
+            let html = styled_line_to_highlighted_html(&ranges, IncludeBackground::No)
+                .unwrap_or_else(|_| line.to_string());
+            output.write_all(html.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn write_pre_tag(
+        &self,
+        output: &mut dyn Write,
+        attributes: HashMap<String, String>,
+    ) -> std::io::Result<()> {
+        comrak::html::write_opening_tag(output, "pre", attributes)
+    }
+
+    fn write_code_tag(
+        &self,
+        output: &mut dyn Write,
+        attributes: HashMap<String, String>,
+    ) -> std::io::Result<()> {
+        comrak::html::write_opening_tag(output, "code", attributes)
+    }
 }
 
 #[cfg(test)]
@@ -1,22 +1,27 @@
 use anyhow::Result;
-use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher, Event};
+use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::fs;
 use std::path::Path;
 use std::sync::mpsc;
 use std::time::Duration;
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Manager};
 
-/// File change event emitted to the frontend
+use crate::services::parser;
+use crate::AppState;
+
+/// File change event emitted to the frontend after the index/link graph
+/// have already been updated, so a listener can just refresh its view.
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct FileChangeEvent {
     pub kind: String,
     pub paths: Vec<String>,
 }
 
-/// Start watching a directory for changes and emit Tauri events
-pub fn start_watcher(
-    app_handle: AppHandle,
-    vault_path: String,
-) -> Result<RecommendedWatcher> {
+/// Start watching a vault directory for changes, keeping the search index
+/// and link graph live without the frontend having to call `reindex_file`.
+/// Returns the watcher handle; dropping it (or storing a new one in
+/// `AppState`) stops the watch.
+pub fn start_watcher(app_handle: AppHandle, vault_path: String) -> Result<RecommendedWatcher> {
     let (tx, rx) = mpsc::channel();
 
     let mut watcher = RecommendedWatcher::new(
@@ -30,45 +35,23 @@ pub fn start_watcher(
 
     watcher.watch(Path::new(&vault_path), RecursiveMode::Recursive)?;
 
-    // Spawn a thread to forward events to Tauri
-    let vault_path_clone = vault_path.clone();
     std::thread::spawn(move || {
-        // Debounce: collect events for 300ms before emitting
+        // Debounce: collect events until 300ms pass with none arriving,
+        // then handle each distinct one, so a save (which fires several
+        // identical modify events in a row) only runs the pipeline once.
+        let mut pending: Vec<Event> = Vec::new();
         loop {
             match rx.recv_timeout(Duration::from_millis(300)) {
                 Ok(event) => {
-                    let paths: Vec<String> = event
-                        .paths
-                        .iter()
-                        .filter_map(|p| {
-                            p.strip_prefix(&vault_path_clone)
-                                .ok()
-                                .map(|rel| rel.to_string_lossy().replace('\\', "/"))
-                        })
-                        .filter(|p| {
-                            // Only emit for markdown files, ignore .git directory
-                            !p.starts_with(".git") && (p.ends_with(".md") || p.ends_with(".markdown"))
-                        })
-                        .collect();
-
-                    if !paths.is_empty() {
-                        let kind = match event.kind {
-                            notify::EventKind::Create(_) => "create",
-                            notify::EventKind::Modify(_) => "modify",
-                            notify::EventKind::Remove(_) => "remove",
-                            _ => continue,
-                        };
-
-                        let _ = app_handle.emit(
-                            "file-change",
-                            FileChangeEvent {
-                                kind: kind.to_string(),
-                                paths,
-                            },
-                        );
+                    if !pending.contains(&event) {
+                        pending.push(event);
+                    }
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    for event in pending.drain(..) {
+                        handle_event(&app_handle, &vault_path, event);
                     }
                 }
-                Err(mpsc::RecvTimeoutError::Timeout) => continue,
                 Err(mpsc::RecvTimeoutError::Disconnected) => break,
             }
         }
@@ -76,3 +59,139 @@ pub fn start_watcher(
 
     Ok(watcher)
 }
+
+fn handle_event(app_handle: &AppHandle, vault_path: &str, event: Event) {
+    let rel_paths: Vec<String> = event
+        .paths
+        .iter()
+        .filter_map(|p| to_relevant_rel_path(p, vault_path))
+        .collect();
+
+    if rel_paths.is_empty() {
+        return;
+    }
+
+    let state = app_handle.state::<AppState>();
+
+    let kind = match event.kind {
+        notify::EventKind::Create(_) => {
+            for rel_path in &rel_paths {
+                reindex_one(&state, vault_path, rel_path);
+            }
+            "create"
+        }
+        notify::EventKind::Modify(notify::event::ModifyKind::Name(_)) => {
+            // A clean rename surfaces as a pair of paths: the old one
+            // first, the new one second. Drop the old entry, (re)index the
+            // new one. Other platforms/filesystems split this into two
+            // separate events (`RenameMode::From` then `RenameMode::To`),
+            // or only deliver one half at all (e.g. a cross-filesystem
+            // move, or a rename that changes the extension so only one
+            // side is still a markdown path we track) — for those, fall
+            // back to checking each surviving path against disk rather
+            // than assuming it's still there to reindex.
+            if let [old_path, new_path] = rel_paths.as_slice() {
+                remove_one(&state, old_path);
+                reindex_one(&state, vault_path, new_path);
+            } else {
+                for rel_path in &rel_paths {
+                    if Path::new(vault_path).join(rel_path).exists() {
+                        reindex_one(&state, vault_path, rel_path);
+                    } else {
+                        remove_one(&state, rel_path);
+                    }
+                }
+            }
+            "rename"
+        }
+        notify::EventKind::Modify(_) => {
+            for rel_path in &rel_paths {
+                reindex_one(&state, vault_path, rel_path);
+            }
+            "modify"
+        }
+        notify::EventKind::Remove(_) => {
+            for rel_path in &rel_paths {
+                remove_one(&state, rel_path);
+            }
+            "remove"
+        }
+        _ => return,
+    };
+
+    // A file change can flip git's status/diff output, so the cached reads
+    // can't be trusted until the next poll re-populates them.
+    state.git_cache.invalidate_all();
+
+    let _ = app_handle.emit(
+        "vault://file-changed",
+        FileChangeEvent {
+            kind: kind.to_string(),
+            paths: rel_paths,
+        },
+    );
+}
+
+/// Only markdown files outside `.git`/`node_modules`/`target` are relevant,
+/// matching the directories `build_tree` skips (`index_vault` skips only
+/// `.git`).
+fn to_relevant_rel_path(path: &Path, vault_path: &str) -> Option<String> {
+    let rel = path
+        .strip_prefix(vault_path)
+        .ok()?
+        .to_string_lossy()
+        .replace('\\', "/");
+
+    if rel
+        .split('/')
+        .any(|c| c == ".git" || c == "node_modules" || c == "target")
+    {
+        return None;
+    }
+
+    if rel.ends_with(".md") || rel.ends_with(".markdown") {
+        Some(rel)
+    } else {
+        None
+    }
+}
+
+fn reindex_one(state: &tauri::State<'_, AppState>, vault_path: &str, rel_path: &str) {
+    let full_path = Path::new(vault_path).join(rel_path);
+    let Ok(content) = fs::read_to_string(&full_path) else {
+        return;
+    };
+    let parsed = parser::parse_note(&content);
+    let modified = fs::metadata(&full_path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    let title = parsed
+        .frontmatter
+        .get("title")
+        .cloned()
+        .unwrap_or_else(|| {
+            Path::new(rel_path)
+                .file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string()
+        });
+
+    if let Some(index) = state.search_index.lock().unwrap().as_ref() {
+        let _ = index.index_note(rel_path, &title, &content, &parsed.tags, modified, &parsed.links);
+    }
+
+    state.link_graph.register_note(rel_path);
+    state.link_graph.update_links(rel_path, parsed.links);
+}
+
+fn remove_one(state: &tauri::State<'_, AppState>, rel_path: &str) {
+    if let Some(index) = state.search_index.lock().unwrap().as_ref() {
+        let _ = index.remove_note(rel_path);
+    }
+    state.link_graph.remove_note(rel_path);
+}